@@ -1,9 +1,18 @@
-use dns_core::DnsServer;
+use dns_core::{
+  DnsServer,
+  DnsServerConfig,
+  enums::{BlockMode, ResolveMode},
+  structs::{BlocklistSource, ConfigReloader, LookAtRecord, LookAtSoa},
+  utils::{parse_generic_record, parse_root_hints, parse_zone_file},
+};
 use serde::Deserialize;
 use std::{
   fs::{metadata, read_to_string},
   io::{Error, ErrorKind},
-  net::Ipv4Addr,
+  net::{IpAddr, SocketAddr},
+  path::{Path, PathBuf},
+  str::FromStr,
+  time::Duration,
 };
 use toml::de::from_str as toml_parse_str;
 
@@ -11,30 +20,150 @@ use toml::de::from_str as toml_parse_str;
 
 #[derive(Deserialize)]
 struct ServerConfig {
+  #[serde(default)]
+  listen: Vec<String>,
   nameservers: Vec<String>,
+  mode: Option<String>,
+  root_hints: Option<String>,
   max_messages: usize,
   max_workers: usize,
+  tcp_timeout: Option<u64>,
+  query_timeout: Option<u64>,
+  cache_size: Option<usize>,
+  negative_ttl: Option<u32>,
+  block_mode: Option<String>,
+  #[serde(default)]
+  sinkhole: Vec<String>,
+  block_ttl: Option<u32>,
+  watch: Option<u64>,
+  chaos: Option<ChaosConfig>,
+  zones: Option<Vec<ZoneConfig>>,
   debug: bool,
 }
 
+#[derive(Deserialize)]
+struct ZoneConfig {
+  zone: String,
+  #[serde(default)]
+  nameservers: Vec<String>,
+  mode: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ChaosConfig {
+  version: Option<String>,
+  hostname: Option<String>,
+  id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct MailExchange {
+  #[serde(default)]
+  priority: u16,
+  host: String,
+}
+
+#[derive(Deserialize)]
+struct SoaConfig {
+  mname: String,
+  rname: String,
+  #[serde(default)]
+  serial: u32,
+  minimum: Option<u32>,
+}
+
 #[derive(Deserialize)]
 struct MultipleDomain {
+  #[serde(default)]
   ipv6: Vec<String>,
+  #[serde(default)]
   ipv4: Vec<String>,
+  #[serde(default)]
+  txt: Vec<String>,
+  #[serde(default)]
+  records: Vec<String>,
+  #[serde(default)]
+  ns: Vec<String>,
+  #[serde(default)]
+  mx: Vec<MailExchange>,
+  soa: Option<SoaConfig>,
+  block_mode: Option<String>,
+  #[serde(default)]
+  sinkhole: Vec<String>,
+  #[serde(default = "enabled")]
+  reverse: bool,
   name: Vec<String>,
 }
 
 #[derive(Deserialize)]
 struct SingleDomain {
+  #[serde(default)]
   ipv6: Vec<String>,
+  #[serde(default)]
   ipv4: Vec<String>,
+  #[serde(default)]
+  txt: Vec<String>,
+  #[serde(default)]
+  records: Vec<String>,
+  #[serde(default)]
+  ns: Vec<String>,
+  #[serde(default)]
+  mx: Vec<MailExchange>,
+  soa: Option<SoaConfig>,
+  block_mode: Option<String>,
+  #[serde(default)]
+  sinkhole: Vec<String>,
+  #[serde(default = "enabled")]
+  reverse: bool,
+  name: String,
+}
+
+#[derive(Deserialize)]
+struct ServiceDomain {
+  #[serde(default)]
+  priority: u16,
+  #[serde(default)]
+  weight: u16,
+  target: String,
+  name: String,
+  port: u16,
+}
+
+#[derive(Deserialize)]
+struct AliasDomain {
+  target: String,
+  name: String,
+}
+
+#[derive(Deserialize)]
+struct ZoneFile {
   name: String,
+  file: String,
+}
+
+#[derive(Deserialize)]
+struct BlocklistConfig {
+  #[serde(default)]
+  files: Vec<String>,
+  #[serde(default)]
+  allow_files: Vec<String>,
+  #[serde(default)]
+  allow: Vec<String>,
+  #[serde(default)]
+  reload: u64,
+  block_mode: Option<String>,
+  #[serde(default)]
+  sinkhole: Vec<String>,
 }
 
 #[derive(Deserialize)]
 struct DomainConfig {
   multiple: Option<Vec<MultipleDomain>>,
   single: Option<Vec<SingleDomain>>,
+  srv: Option<Vec<ServiceDomain>>,
+  cname: Option<Vec<AliasDomain>>,
+  zone: Option<Vec<ZoneFile>>,
+  blocklist: Option<BlocklistConfig>,
 }
 
 #[derive(Deserialize)]
@@ -43,62 +172,262 @@ struct Config {
   server: ServerConfig,
 }
 
-// * >>> *
+fn enabled() -> bool { true }
 
-#[tokio::main(flavor = "multi_thread", worker_threads = 10)]
-async fn main() -> Result<(), Error> {
-  if !metadata("server.toml").is_ok() {
+fn parse_records(records: &[String]) -> Result<Vec<(u16, Vec<u8>)>, Error> {
+  records
+    .iter()
+    .map(|record| {
+      parse_generic_record(record)
+        .map_err(|e| Error::new(ErrorKind::InvalidData, e.to_string()))
+    })
+    .collect()
+}
+
+fn parse_mx(mx: &[MailExchange]) -> Vec<(u16, String)> {
+  mx.iter().map(|mx| (mx.priority, mx.host.clone())).collect()
+}
+
+fn parse_soa(soa: &Option<SoaConfig>, negative_ttl: u32) -> Option<LookAtSoa> {
+  soa.as_ref().map(|soa| {
+    LookAtSoa::new(
+      soa.mname.clone(),
+      soa.rname.clone(),
+      soa.serial,
+      soa.minimum.unwrap_or(negative_ttl),
+    )
+  })
+}
+
+fn parse_addrs(addrs: &[String]) -> Result<Vec<SocketAddr>, Error> {
+  addrs
+    .iter()
+    .map(|addr| {
+      // a bare address uses the default DNS port
+      addr
+        .parse::<SocketAddr>()
+        .or_else(|_| addr.parse::<IpAddr>().map(|ip| SocketAddr::new(ip, 53)))
+        .map_err(|_| {
+          Error::new(
+            ErrorKind::InvalidInput,
+            format!("Invalid address '{}'!", addr),
+          )
+        })
+    })
+    .collect()
+}
+
+fn parse_ips<T: FromStr>(ips: &[String]) -> Result<Vec<T>, Error> {
+  ips
+    .iter()
+    .map(|ip| {
+      ip.parse::<T>().map_err(|_| {
+        Error::new(ErrorKind::InvalidInput, format!("Invalid address '{}'!", ip))
+      })
+    })
+    .collect()
+}
+
+fn parse_mode(mode: &str) -> Result<ResolveMode, Error> {
+  ResolveMode::parse(mode).ok_or_else(|| {
+    Error::new(ErrorKind::InvalidInput, format!("Invalid mode '{}'!", mode))
+  })
+}
+
+fn parse_block_mode(
+  mode: &Option<String>,
+  sinkhole: &[String],
+) -> Result<Option<BlockMode>, Error> {
+  let Some(mode) = mode else {
+    return Ok(None);
+  };
+  let sinkhole: Vec<IpAddr> = parse_ips(sinkhole)?;
+  if mode.eq_ignore_ascii_case("sinkhole") && sinkhole.is_empty() {
+    return Err(Error::new(
+      ErrorKind::InvalidInput,
+      "Block mode 'sinkhole' needs at least one sinkhole address!",
+    ));
+  }
+
+  BlockMode::parse(mode, sinkhole).map(Some).ok_or_else(|| {
+    Error::new(ErrorKind::InvalidInput, format!("Invalid block mode '{}'!", mode))
+  })
+}
+
+fn read_config(path: &Path) -> Result<Config, Error> {
+  if metadata(path).is_err() {
     return Err(Error::new(
       ErrorKind::NotFound,
-      "Failed to open configuration file 'server.toml'!",
+      format!("Failed to open configuration file '{}'!", path.display()),
     ));
   }
-  let config_string: String = read_to_string("server.toml").unwrap();
-
-  match toml_parse_str::<Config>(&config_string) {
-    Ok(config) => {
-      let nameservers: Vec<Ipv4Addr> = config
-        .server
-        .nameservers
-        .iter()
-        .map(|s| s.parse().unwrap())
-        .collect();
-
-      // *
-
-      let mut server: DnsServer = DnsServer::new(
-        nameservers,
-        config.server.max_workers,
-        config.server.max_messages,
-        config.server.debug,
-      )?;
-
-      if let Some(singles) = &config.domains.single {
-        for domain in singles {
-          server.config.look_at(
-            domain.name.clone(),
-            domain.ipv4.iter().map(|ip| ip.parse().unwrap()).collect(),
-            domain.ipv6.iter().map(|ip| ip.parse().unwrap()).collect(),
-          );
-        }
-      }
-      if let Some(multiples) = &config.domains.multiple {
-        for domain in multiples {
-          server.config.look_many(
-            domain.name.clone(),
-            domain.ipv4.iter().map(|ip| ip.parse().unwrap()).collect(),
-            domain.ipv6.iter().map(|ip| ip.parse().unwrap()).collect(),
-          );
-        }
-      }
-
-      server.start().await
-    },
-    Err(e) => {
-      return Err(Error::new(
-        ErrorKind::InvalidData,
-        format!("Failed to parse configuration file: {}", e.message()),
-      ));
-    },
+  let config_string: String = read_to_string(path)?;
+
+  toml_parse_str::<Config>(&config_string).map_err(|e| {
+    Error::new(
+      ErrorKind::InvalidData,
+      format!("Failed to parse configuration file: {}", e.message()),
+    )
+  })
+}
+
+/// Builds the server configuration from the file. Listen addresses, workers, timeouts
+/// of upstream queries, the cache and debug messages are set up once, at startup.
+fn build_config(config: &Config) -> Result<DnsServerConfig, Error> {
+  let mut server_config: DnsServerConfig = DnsServerConfig::new(
+    parse_addrs(&config.server.nameservers)?,
+    config.server.max_workers,
+    config.server.max_messages,
+  );
+
+  if let Some(mode) = &config.server.mode {
+    server_config.mode = parse_mode(mode)?;
+  }
+  if let Some(path) = &config.server.root_hints {
+    server_config.root_hints = parse_root_hints(&read_to_string(path)?)
+      .map_err(|e| Error::new(ErrorKind::InvalidData, e.to_string()))?;
+  }
+  if let Some(zones) = &config.server.zones {
+    for zone in zones {
+      server_config.forward_zone(
+        zone.zone.clone(),
+        parse_addrs(&zone.nameservers)?,
+        zone.mode.as_deref().map_or(Ok(ResolveMode::Forward), parse_mode)?,
+      );
+    }
+  }
+  if let Some(tcp_timeout) = config.server.tcp_timeout {
+    server_config.tcp_idle_timeout = Duration::from_secs(tcp_timeout);
+  }
+  if let Some(query_timeout) = config.server.query_timeout {
+    server_config.query_timeout = Duration::from_secs(query_timeout);
+  }
+  if let Some(cache_size) = config.server.cache_size {
+    server_config.cache_size = cache_size;
+  }
+  if let Some(negative_ttl) = config.server.negative_ttl {
+    server_config.negative_ttl = negative_ttl;
   }
+  if let Some(mode) =
+    parse_block_mode(&config.server.block_mode, &config.server.sinkhole)?
+  {
+    server_config.block_mode = mode;
+  }
+  if let Some(block_ttl) = config.server.block_ttl {
+    server_config.block_ttl = block_ttl;
+  }
+  if let Some(chaos) = &config.server.chaos {
+    if let Some(version) = &chaos.version {
+      server_config.look_chaos("version.bind", version.clone());
+      server_config.look_chaos("version.server", version.clone());
+    }
+    if let Some(hostname) = &chaos.hostname {
+      server_config.look_chaos("hostname.bind", hostname.clone());
+    }
+    if let Some(id) = &chaos.id {
+      server_config.look_chaos("id.server", id.clone());
+    }
+  }
+
+  if let Some(singles) = &config.domains.single {
+    for domain in singles {
+      server_config.look_at(LookAtRecord {
+        txt_records: domain.txt.clone(),
+        raw_records: parse_records(&domain.records)?,
+        ns_hosts: domain.ns.clone(),
+        mx_hosts: parse_mx(&domain.mx),
+        soa: parse_soa(&domain.soa, server_config.negative_ttl),
+        block_mode: parse_block_mode(&domain.block_mode, &domain.sinkhole)?,
+        reverse: domain.reverse,
+        ..LookAtRecord::new(
+          vec![domain.name.clone()],
+          parse_ips(&domain.ipv4)?,
+          parse_ips(&domain.ipv6)?,
+        )
+      });
+    }
+  }
+  if let Some(multiples) = &config.domains.multiple {
+    for domain in multiples {
+      server_config.look_at(LookAtRecord {
+        txt_records: domain.txt.clone(),
+        raw_records: parse_records(&domain.records)?,
+        ns_hosts: domain.ns.clone(),
+        mx_hosts: parse_mx(&domain.mx),
+        soa: parse_soa(&domain.soa, server_config.negative_ttl),
+        block_mode: parse_block_mode(&domain.block_mode, &domain.sinkhole)?,
+        reverse: domain.reverse,
+        ..LookAtRecord::new(
+          domain.name.clone(),
+          parse_ips(&domain.ipv4)?,
+          parse_ips(&domain.ipv6)?,
+        )
+      });
+    }
+  }
+  if let Some(services) = &config.domains.srv {
+    for service in services {
+      server_config.look_service(
+        service.name.clone(),
+        service.target.clone(),
+        service.port,
+        service.priority,
+        service.weight,
+      );
+    }
+  }
+  if let Some(aliases) = &config.domains.cname {
+    for alias in aliases {
+      server_config.look_alias(&alias.name, alias.target.clone());
+    }
+  }
+  if let Some(blocklist) = &config.domains.blocklist {
+    let source: BlocklistSource = BlocklistSource::new(
+      blocklist.files.iter().map(PathBuf::from).collect(),
+      blocklist.allow_files.iter().map(PathBuf::from).collect(),
+      blocklist.allow.clone(),
+      Duration::from_secs(blocklist.reload),
+      parse_block_mode(&blocklist.block_mode, &blocklist.sinkhole)?,
+    );
+    source
+      .load()
+      .map_err(|e| Error::new(ErrorKind::InvalidData, e.to_string()))?;
+    server_config.blocklist = Some(source);
+  }
+  if let Some(zones) = &config.domains.zone {
+    for zone in zones {
+      server_config.serve_zone(
+        parse_zone_file(&zone.name, Path::new(&zone.file))
+          .map_err(|e| Error::new(ErrorKind::InvalidData, e.to_string()))?,
+      );
+    }
+  }
+
+  Ok(server_config)
+}
+
+// * >>> *
+
+#[tokio::main(flavor = "multi_thread", worker_threads = 10)]
+async fn main() -> Result<(), Error> {
+  let config_path: PathBuf = PathBuf::from("server.toml");
+  let config: Config = read_config(&config_path)?;
+
+  let mut server: DnsServer = DnsServer::new(
+    parse_addrs(&config.server.listen)?,
+    parse_addrs(&config.server.nameservers)?,
+    config.server.max_workers,
+    config.server.max_messages,
+    config.server.debug,
+  )?;
+  server.config = build_config(&config)?;
+
+  // a new configuration is only swapped in once it is read and built without errors
+  server.reloader = Some(ConfigReloader::new(
+    config_path,
+    Duration::from_secs(config.server.watch.unwrap_or(0)),
+    |path: &Path| read_config(path).and_then(|config| build_config(&config)),
+  ));
+
+  server.start().await
 }
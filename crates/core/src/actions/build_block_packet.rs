@@ -0,0 +1,87 @@
+use crate::{
+  enums::{
+    BlockMode,
+    BytePacketError as Error,
+    DnsRecord,
+    QueryClass,
+    QueryType,
+    ResultCode,
+  },
+  structs::{DnsPacket, DnsQuestion},
+};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+// * >>> *
+
+/// Answers a question for a blocked name the way the block mode asks. Addresses are
+/// only sent for A and AAAA queries, other types get NODATA. Negative answers carry
+/// an SOA owned by the name so that clients cache them for `ttl` (RFC 2308 section 5).
+pub fn build_block_packet(
+  question: &DnsQuestion,
+  mode: &BlockMode,
+  ttl: u32,
+) -> Result<DnsPacket, Error> {
+  let mut packet: DnsPacket = DnsPacket::new();
+  packet.header.rescode = ResultCode::NoError;
+
+  let addrs: Vec<IpAddr> = match mode {
+    BlockMode::Refused => {
+      packet.header.rescode = ResultCode::Refused;
+      return Ok(packet);
+    },
+    BlockMode::NxDomain => {
+      packet.header.rescode = ResultCode::NxDomain;
+      Vec::new()
+    },
+    BlockMode::NoData => Vec::new(),
+    BlockMode::NullIp => {
+      vec![IpAddr::V4(Ipv4Addr::UNSPECIFIED), IpAddr::V6(Ipv6Addr::UNSPECIFIED)]
+    },
+    BlockMode::Sinkhole(addrs) => addrs.clone(),
+  };
+
+  packet.header.authoritative_answer = true;
+  let domain: &String = &question.name;
+  let wanted = |qtype: QueryType| {
+    question.qtype == qtype || question.qtype == QueryType::UNKNOWN(0xFF)
+  };
+
+  for addr in addrs {
+    match addr {
+      IpAddr::V4(address) if wanted(QueryType::A) => {
+        packet.answers.push(DnsRecord::A {
+          domain: domain.clone(),
+          address,
+          class: QueryClass::IN,
+          ttl,
+        });
+      },
+      IpAddr::V6(address) if wanted(QueryType::AAAA) => {
+        packet.answers.push(DnsRecord::AAAA {
+          domain: domain.clone(),
+          address,
+          class: QueryClass::IN,
+          ttl,
+        });
+      },
+      _ => {},
+    }
+  }
+
+  if packet.answers.is_empty() {
+    packet.authorities.push(DnsRecord::SOA {
+      domain: domain.clone(),
+      mname: "localhost".to_string(),
+      rname: "nobody.invalid".to_string(),
+      serial: 0x00,
+      refresh: ttl,
+      retry: ttl,
+      expire: ttl,
+      minimum: ttl,
+      class: QueryClass::IN,
+      ttl,
+    });
+  }
+
+  Ok(packet)
+}
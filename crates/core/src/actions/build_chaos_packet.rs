@@ -0,0 +1,32 @@
+use crate::{
+  enums::{BytePacketError as Error, DnsRecord, QueryClass, ResultCode},
+  structs::DnsPacket,
+};
+
+// * >>> *
+
+pub fn build_chaos_packet(name: &str, value: &str) -> Result<DnsPacket, Error> {
+  const MAX_STRING_LENGTH: usize = 0xFF;
+  let mut packet: DnsPacket = DnsPacket::new();
+  packet.header.authoritative_answer = true;
+  packet.header.rescode = ResultCode::NoError;
+
+  // an empty identity is still one character-string, a TXT record has at least one
+  let mut data: Vec<Vec<u8>> = value
+    .as_bytes()
+    .chunks(MAX_STRING_LENGTH)
+    .map(<[u8]>::to_vec)
+    .collect();
+  if data.is_empty() {
+    data.push(Vec::new());
+  }
+
+  packet.answers.push(DnsRecord::TXT {
+    domain: name.to_string(),
+    data,
+    class: QueryClass::CH,
+    ttl: 0x00,
+  });
+
+  Ok(packet)
+}
@@ -0,0 +1,22 @@
+use crate::{
+  enums::{BytePacketError as Error, DnsRecord, QueryClass, ResultCode},
+  structs::DnsPacket,
+};
+
+// * >>> *
+
+pub fn build_cname_packet(name: &str, target: &str) -> Result<DnsPacket, Error> {
+  const DEFAULT_TTL: u32 = 0xE10;
+  let mut packet: DnsPacket = DnsPacket::new();
+  packet.header.authoritative_answer = true;
+  packet.header.rescode = ResultCode::NoError;
+
+  packet.answers.push(DnsRecord::CNAME {
+    domain: name.to_string(),
+    host: target.to_string(),
+    class: QueryClass::IN,
+    ttl: DEFAULT_TTL,
+  });
+
+  Ok(packet)
+}
@@ -1,89 +1,137 @@
 use crate::{
-  enums::{BytePacketError as Error, DnsRecord, QueryType, ResultCode},
-  structs::{DnsHeader, DnsPacket, DnsQuestion, LookAtRecord},
+  enums::{BytePacketError as Error, DnsRecord, QueryClass, QueryType, ResultCode},
+  structs::{DnsPacket, DnsQuestion, LookAtRecord},
+  utils::in_zone,
 };
 
 // * >>> *
 
-pub fn build_dns_packet(record: &LookAtRecord) -> Result<DnsPacket, Error> {
+/// Answers a question from a local entry with only the RRset of the asked type, or
+/// with NODATA (and the SOA, when configured) if the entry has none
+pub fn build_dns_packet(
+  record: &LookAtRecord,
+  question: &DnsQuestion,
+) -> Result<DnsPacket, Error> {
   const DEFAULT_TTL: u32 = 0xE10;
+  const MAX_STRING_LENGTH: usize = 0xFF;
   let mut packet: DnsPacket = DnsPacket::new();
+  packet.header.authoritative_answer = true;
+  packet.header.rescode = ResultCode::NoError;
 
-  let num_domains: usize = record.domains.len();
-  let has_ipv4: bool = !record.ipv4_addrs.is_empty();
-  let has_ipv6: bool = !record.ipv6_addrs.is_empty();
+  let domain: &String = &question.name;
+  let wanted = |qtype: QueryType| {
+    question.qtype == qtype || question.qtype == QueryType::UNKNOWN(0xFF)
+  };
 
-  let mut num_questions: usize = 0x00;
-  if has_ipv4 {
-    num_questions += num_domains;
+  if wanted(QueryType::A) {
+    for ipv4 in &record.ipv4_addrs {
+      packet.answers.push(DnsRecord::A {
+        domain: domain.clone(),
+        address: *ipv4,
+        class: QueryClass::IN,
+        ttl: DEFAULT_TTL,
+      });
+    }
   }
-  if has_ipv6 {
-    num_questions += num_domains;
+
+  if wanted(QueryType::AAAA) {
+    for ipv6 in &record.ipv6_addrs {
+      packet.answers.push(DnsRecord::AAAA {
+        domain: domain.clone(),
+        address: *ipv6,
+        class: QueryClass::IN,
+        ttl: DEFAULT_TTL,
+      });
+    }
   }
 
-  let num_answers: usize =
-    num_domains * (record.ipv4_addrs.len() + record.ipv6_addrs.len());
-  packet.header = DnsHeader {
-    id: 0x29A, // random ID
-    recursion_desired: true,
-    truncated_message: false,
-    authoritative_answer: true,
-    opcode: 0,
-    response: true,
-    rescode: ResultCode::NoError,
-    checking_disabled: false,
-    authed_data: true,
-    z: false,
-    recursion_available: true,
-    questions: num_questions as u16,
-    answers: num_answers as u16,
-    authoritative_entries: num_domains as u16,
-    resource_entries: num_domains as u16,
-  };
+  if wanted(QueryType::TXT) {
+    for txt in &record.txt_records {
+      // long values are split into several character-strings of the same record
+      let mut data: Vec<Vec<u8>> = txt
+        .as_bytes()
+        .chunks(MAX_STRING_LENGTH)
+        .map(<[u8]>::to_vec)
+        .collect();
+      if data.is_empty() {
+        data.push(Vec::new());
+      }
 
-  for domain in &record.domains {
-    if has_ipv4 {
-      packet.questions.push(DnsQuestion {
-        name: domain.clone(),
-        qtype: QueryType::A,
+      packet.answers.push(DnsRecord::TXT {
+        domain: domain.clone(),
+        data,
+        class: QueryClass::IN,
+        ttl: DEFAULT_TTL,
       });
     }
+  }
 
-    if has_ipv6 {
-      packet.questions.push(DnsQuestion {
-        name: domain.clone(),
-        qtype: QueryType::AAAA,
+  if wanted(QueryType::NS) {
+    for host in &record.ns_hosts {
+      packet.answers.push(DnsRecord::NS {
+        domain: domain.clone(),
+        host: host.clone(),
+        class: QueryClass::IN,
+        ttl: DEFAULT_TTL,
       });
     }
+  }
 
-    for ipv4 in &record.ipv4_addrs {
-      packet.answers.push(DnsRecord::A {
+  if wanted(QueryType::MX) {
+    for (priority, host) in &record.mx_hosts {
+      packet.answers.push(DnsRecord::MX {
+        priority: *priority,
         domain: domain.clone(),
-        address: *ipv4,
+        host: host.clone(),
+        class: QueryClass::IN,
         ttl: DEFAULT_TTL,
       });
     }
+  }
 
-    for ipv6 in &record.ipv6_addrs {
-      packet.answers.push(DnsRecord::AAAA {
+  for (qtype, data) in &record.raw_records {
+    if wanted(QueryType::from(*qtype)) {
+      packet.answers.push(DnsRecord::UNKNOWN {
+        data: data.clone(),
         domain: domain.clone(),
-        address: *ipv6,
+        qtype: *qtype,
+        class: QueryClass::IN,
         ttl: DEFAULT_TTL,
       });
     }
+  }
 
-    packet.authorities.push(DnsRecord::NS {
-      domain: domain.clone(),
-      host: format!("ns1.{}", domain),
-      ttl: DEFAULT_TTL,
-    });
+  // the SOA belongs to the configured name, not to the names a wildcard covers
+  let Some(soa) = &record.soa else {
+    return Ok(packet);
+  };
+  let owner: &str = record
+    .domains
+    .iter()
+    .map(|d| d.strip_prefix("*.").unwrap_or(d))
+    .filter(|d| in_zone(domain, d))
+    .max_by_key(|d| d.len())
+    .unwrap_or(domain);
+  let soa_record = |ttl: u32| DnsRecord::SOA {
+    domain: owner.to_string(),
+    mname: soa.mname.clone(),
+    rname: soa.rname.clone(),
+    serial: soa.serial,
+    refresh: soa.refresh,
+    retry: soa.retry,
+    expire: soa.expire,
+    minimum: soa.minimum,
+    class: QueryClass::IN,
+    ttl,
+  };
 
-    packet.resources.push(DnsRecord::MX {
-      domain: domain.clone(),
-      priority: 10,
-      host: format!("mail.{}", domain),
-      ttl: DEFAULT_TTL,
-    });
+  let at_owner: bool =
+    owner.trim_end_matches('.').eq_ignore_ascii_case(domain.trim_end_matches('.'));
+  if wanted(QueryType::SOA) && at_owner {
+    packet.answers.push(soa_record(DEFAULT_TTL));
+  } else if packet.answers.is_empty() {
+    // negative answers are cached for the SOA minimum at most (RFC 2308 section 5)
+    packet.authorities.push(soa_record(DEFAULT_TTL.min(soa.minimum)));
   }
 
   Ok(packet)
@@ -0,0 +1,24 @@
+use crate::{
+  enums::{BytePacketError as Error, DnsRecord, QueryClass, ResultCode},
+  structs::DnsPacket,
+};
+
+// * >>> *
+
+pub fn build_ptr_packet(name: &str, hosts: &[String]) -> Result<DnsPacket, Error> {
+  const DEFAULT_TTL: u32 = 0xE10;
+  let mut packet: DnsPacket = DnsPacket::new();
+  packet.header.authoritative_answer = true;
+  packet.header.rescode = ResultCode::NoError;
+
+  for host in hosts {
+    packet.answers.push(DnsRecord::PTR {
+      domain: name.to_string(),
+      host: host.clone(),
+      class: QueryClass::IN,
+      ttl: DEFAULT_TTL,
+    });
+  }
+
+  Ok(packet)
+}
@@ -0,0 +1,55 @@
+use crate::{
+  enums::{BytePacketError as Error, DnsRecord, QueryClass, ResultCode},
+  structs::{DnsPacket, DnsServerConfig, LookAtService},
+};
+
+// * >>> *
+
+pub fn build_srv_packet(
+  services: &[LookAtService],
+  config: &DnsServerConfig,
+) -> Result<DnsPacket, Error> {
+  const DEFAULT_TTL: u32 = 0xE10;
+  let mut packet: DnsPacket = DnsPacket::new();
+  packet.header.authoritative_answer = true;
+  packet.header.rescode = ResultCode::NoError;
+
+  for service in services {
+    packet.answers.push(DnsRecord::SRV {
+      priority: service.priority,
+      weight: service.weight,
+      port: service.port,
+      domain: service.name.clone(),
+      host: service.target.clone(),
+      class: QueryClass::IN,
+      ttl: DEFAULT_TTL,
+    });
+
+    // addresses of locally defined targets are sent along as additional records
+    let Some(record) = config.find_look_at(&service.target) else {
+      continue;
+    };
+    if record.is_blocked() {
+      continue;
+    }
+
+    for ipv4 in &record.ipv4_addrs {
+      packet.resources.push(DnsRecord::A {
+        domain: service.target.clone(),
+        address: *ipv4,
+        class: QueryClass::IN,
+        ttl: DEFAULT_TTL,
+      });
+    }
+    for ipv6 in &record.ipv6_addrs {
+      packet.resources.push(DnsRecord::AAAA {
+        domain: service.target.clone(),
+        address: *ipv6,
+        class: QueryClass::IN,
+        ttl: DEFAULT_TTL,
+      });
+    }
+  }
+
+  Ok(packet)
+}
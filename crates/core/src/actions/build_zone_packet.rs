@@ -0,0 +1,164 @@
+use crate::{
+  enums::{BytePacketError as Error, DnsRecord, QueryType, ResultCode},
+  structs::{DnsPacket, DnsQuestion, Zone},
+  utils::in_zone,
+};
+
+// * >>> *
+
+/// Answers a question from a zone, following the RFC 1034 section 4.3.2 algorithm:
+/// referrals at delegations, the RRset or a CNAME for existing names and for names a
+/// wildcard covers, and NODATA or NXDOMAIN with the SOA for the rest
+pub fn build_zone_packet(
+  zone: &Zone,
+  question: &DnsQuestion,
+) -> Result<DnsPacket, Error> {
+  let name: String = question.name.trim_end_matches('.').to_lowercase();
+  let mut packet: DnsPacket = DnsPacket::new();
+  packet.header.rescode = ResultCode::NoError;
+
+  // names at or below a delegation belong to the child zone, which is not ours
+  if let Some(cut) = find_delegation(zone, &name, question.qtype) {
+    packet.authorities = zone.rrset(&cut, QueryType::NS);
+    packet.resources = additional_records(zone, &packet.authorities);
+    return Ok(packet);
+  }
+
+  packet.header.authoritative_answer = true;
+  let records: Vec<DnsRecord> = match zone.lookup(&name) {
+    Some(records) => records.to_vec(),
+    None => match wildcard_records(zone, &name, &question.name) {
+      Some(records) => records,
+      None => {
+        if !zone.has_descendants(&name) {
+          packet.header.rescode = ResultCode::NxDomain;
+        }
+        packet.authorities = negative_soa(zone);
+        return Ok(packet);
+      },
+    },
+  };
+
+  let wanted = |qtype: QueryType| {
+    question.qtype == QueryType::UNKNOWN(0xFF) || qtype == question.qtype
+  };
+  let mut answers: Vec<DnsRecord> =
+    records.iter().filter(|r| wanted(r.qtype())).cloned().collect();
+  if answers.is_empty() {
+    // the target of a CNAME is resolved by following it
+    answers = records
+      .into_iter()
+      .filter(|r| r.qtype() == QueryType::CNAME)
+      .collect();
+  }
+  if answers.is_empty() {
+    packet.authorities = negative_soa(zone);
+    return Ok(packet);
+  }
+
+  packet.answers = answers;
+  if name != zone.origin || question.qtype != QueryType::NS {
+    packet.authorities = zone.rrset(&zone.origin, QueryType::NS);
+  }
+  let targets: Vec<DnsRecord> =
+    packet.answers.iter().chain(&packet.authorities).cloned().collect();
+  packet.resources = additional_records(zone, &targets);
+
+  Ok(packet)
+}
+
+// *
+
+/// Finds the highest delegation (NS records below the apex) on the way to a name. DS
+/// records belong to the parent side of the delegation.
+fn find_delegation(zone: &Zone, name: &str, qtype: QueryType) -> Option<String> {
+  let mut names: Vec<&str> = Vec::new();
+  let mut current: &str = name;
+  while current != zone.origin && !current.is_empty() {
+    names.push(current);
+    current = current.split_once('.').map_or("", |(_, parent)| parent);
+  }
+
+  names
+    .into_iter()
+    .rev()
+    .filter(|cut| *cut != name || qtype != QueryType::UNKNOWN(0x2B))
+    .find(|cut| !zone.rrset(cut, QueryType::NS).is_empty())
+    .map(str::to_string)
+}
+
+/// Synthesizes the records of a missing name from the wildcard at its closest
+/// encloser (RFC 4592 section 3.3.1). Names that exist without records, as empty
+/// non-terminals, are never covered.
+fn wildcard_records(zone: &Zone, name: &str, owner: &str) -> Option<Vec<DnsRecord>> {
+  if zone.has_descendants(name) {
+    return None;
+  }
+
+  let mut encloser: &str = name;
+  while encloser != zone.origin
+    && zone.lookup(encloser).is_none()
+    && !zone.has_descendants(encloser)
+  {
+    encloser = encloser.split_once('.').map_or("", |(_, parent)| parent);
+  }
+
+  let wildcard: String = match encloser.is_empty() {
+    true => "*".to_string(),
+    false => format!("*.{}", encloser),
+  };
+  let records: Vec<DnsRecord> = zone
+    .lookup(&wildcard)?
+    .iter()
+    .cloned()
+    .map(|mut record| {
+      record.set_domain(owner.trim_end_matches('.'));
+      record
+    })
+    .collect();
+
+  Some(records)
+}
+
+/// The SOA sent with negative answers, with the TTL capped by its minimum field
+/// (RFC 2308 section 3)
+fn negative_soa(zone: &Zone) -> Vec<DnsRecord> {
+  zone
+    .soa()
+    .map(|mut soa| {
+      if let DnsRecord::SOA { minimum, ttl, .. } = &mut soa {
+        *ttl = (*ttl).min(*minimum);
+      }
+      soa
+    })
+    .into_iter()
+    .collect()
+}
+
+/// Addresses from the zone for the hosts named by NS, MX and SRV records
+fn additional_records(zone: &Zone, records: &[DnsRecord]) -> Vec<DnsRecord> {
+  let mut resources: Vec<DnsRecord> = Vec::new();
+  for record in records {
+    let host: &str = match record {
+      DnsRecord::NS { host, .. }
+      | DnsRecord::MX { host, .. }
+      | DnsRecord::SRV { host, .. } => host,
+      _ => continue,
+    };
+    if !in_zone(host, &zone.origin) {
+      continue;
+    }
+
+    for address in zone
+      .rrset(host, QueryType::A)
+      .into_iter()
+      .chain(zone.rrset(host, QueryType::AAAA))
+    {
+      if !resources.contains(&address) {
+        resources.push(address);
+      }
+    }
+  }
+
+  resources
+}
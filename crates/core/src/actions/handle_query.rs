@@ -1,58 +1,25 @@
 use crate::{
-  actions::recursive_lookup,
-  enums::{BytePacketError as Error, ResultCode},
-  structs::{BytePacketBuffer as Buffer, DnsPacket, DnsServerConfig},
+  actions::resolve_query,
+  enums::BytePacketError as Error,
+  structs::{DnsCache, DnsPacket, DnsServerConfig, LookupClient},
   utils::*,
 };
-use std::net::{SocketAddr, UdpSocket};
+use std::net::SocketAddr;
+use tokio::net::UdpSocket;
 
 // * >>> *
 
-pub fn handle_query(
+pub async fn handle_query(
   config: &DnsServerConfig,
-  client_socket: &UdpSocket,
+  cache: &DnsCache,
+  client_socket: &LookupClient,
   socket: &UdpSocket,
   buffer: Vec<u8>,
   src: SocketAddr,
   debug: bool,
 ) -> Result<(), Error> {
-  let mut req_buffer: Buffer = Buffer::new();
-  req_buffer.buffer[..buffer.len()].copy_from_slice(&buffer);
-
-  let request: DnsPacket = DnsPacket::from_buffer(&mut req_buffer)?;
-  let mut response: DnsPacket = DnsPacket::new();
-  response.header.id = request.header.id;
-  response.header.recursion_desired = true;
-  response.header.recursion_available = true;
-  response.header.response = true;
-
-  let Some(question) = request.questions.first() else {
-    response.header.rescode = ResultCode::FormError;
-    return send_response(socket, &mut response, src);
-  };
-
-  if let Some(mut result_packet) = handle_look_at(&config, question, &mut response, debug)
-  {
-    return send_response(socket, &mut result_packet, src);
-  }
-
-  match recursive_lookup(
-    &client_socket,
-    config.nameservers.clone(),
-    &question.name,
-    question.qtype,
-  ) {
-    Ok(mut result) => {
-      response.questions.push(question.clone());
-      response.header.rescode = result.header.rescode;
-      response.answers.append(&mut result.answers);
-      response.authorities.append(&mut result.authorities);
-      response.resources.append(&mut result.resources);
-    },
-    Err(_) => {
-      response.header.rescode = ResultCode::ServerFail;
-    },
-  }
-
-  send_response(socket, &mut response, src)
+  let request: DnsPacket = DnsPacket::from_bytes(&buffer)?;
+  let mut response: DnsPacket =
+    resolve_query(config, cache, client_socket, &request, debug).await?;
+  send_response(socket, &mut response, src, request.udp_payload_size()).await
 }
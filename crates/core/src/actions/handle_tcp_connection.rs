@@ -0,0 +1,220 @@
+use crate::{
+  actions::resolve_query,
+  enums::{BytePacketError as Error, ResultCode},
+  structs::{DnsCache, DnsPacket, DnsServerConfig, LookupClient, SharedConfig},
+  utils::encode_tcp_response,
+};
+use std::{
+  io::ErrorKind,
+  net::SocketAddr,
+  sync::Arc,
+  time::Duration,
+};
+use tokio::{
+  io::{AsyncReadExt, AsyncWriteExt},
+  net::TcpStream,
+  spawn,
+  sync::{OwnedSemaphorePermit, Semaphore, mpsc},
+  time::timeout,
+};
+
+// * >>> *
+
+pub const MAX_TCP_PIPELINE: usize = 0x10;
+
+/// Answers the length-prefixed queries of a connection (RFC 1035 section 4.2.2).
+/// At most `MAX_TCP_PIPELINE` queries are resolved at once, further ones are not read
+/// from the connection until one of them is answered.
+pub async fn handle_tcp_connection(
+  shared_config: SharedConfig,
+  cache: Arc<DnsCache>,
+  client_socket: Arc<LookupClient>,
+  stream: TcpStream,
+  src: SocketAddr,
+  debug: bool,
+) -> Result<(), Error> {
+  let (mut reader, mut writer) = stream.into_split();
+  let (frame_tx, mut frame_rx) = mpsc::channel::<Vec<u8>>(MAX_TCP_PIPELINE);
+  let pipeline: Arc<Semaphore> = Arc::new(Semaphore::new(MAX_TCP_PIPELINE));
+
+  // responses of pipelined queries are written back as soon as they are ready
+  let writer_task = spawn(async move {
+    while let Some(frame) = frame_rx.recv().await {
+      if writer.write_all(&frame).await.is_err() {
+        break;
+      }
+    }
+    let _ = writer.shutdown().await;
+  });
+
+  loop {
+    let idle_timeout: Duration = shared_config.load().tcp_idle_timeout;
+    let mut len_buffer: [u8; 2] = [0x00; 2];
+    match timeout(idle_timeout, reader.read_exact(&mut len_buffer)).await {
+      Ok(Ok(_)) => {},
+      Ok(Err(e)) if e.kind() == ErrorKind::UnexpectedEof => break,
+      Ok(Err(e)) => return Err(Error::IoError(e)),
+      Err(_) => {
+        if debug {
+          println!("[DEBUG]: Closing idle TCP connection from {}", src);
+        }
+        break;
+      },
+    }
+
+    // the length prefix allows messages of up to 65535 bytes
+    let length: usize = u16::from_be_bytes(len_buffer) as usize;
+    let mut data: Vec<u8> = vec![0x00; length];
+    match timeout(idle_timeout, reader.read_exact(&mut data)).await {
+      Ok(Ok(_)) => {},
+      Ok(Err(e)) => return Err(Error::IoError(e)),
+      Err(_) => break,
+    }
+
+    // the next query is only read once there is room in the pipeline
+    let Ok(permit) = Arc::clone(&pipeline).acquire_owned().await else {
+      break;
+    };
+
+    // every query is answered with the configuration current when it arrives
+    let config: Arc<DnsServerConfig> = shared_config.load();
+    let cache: Arc<DnsCache> = Arc::clone(&cache);
+    let client_socket: Arc<LookupClient> = Arc::clone(&client_socket);
+    let frame_tx: mpsc::Sender<Vec<u8>> = frame_tx.clone();
+    spawn(async move {
+      let _permit: OwnedSemaphorePermit = permit;
+      let frame: Vec<u8> =
+        match answer_frame(&config, &cache, &client_socket, &data, debug).await {
+          Ok(frame) => frame,
+          Err(e) => {
+            if debug {
+              println!("[DEBUG]: Error handling TCP query from {}: {}", src, e);
+            }
+            return;
+          },
+        };
+      let _ = frame_tx.send(frame).await;
+    });
+  }
+
+  drop(frame_tx);
+  let _ = writer_task.await;
+  Ok(())
+}
+
+// *
+
+async fn answer_frame(
+  config: &DnsServerConfig,
+  cache: &DnsCache,
+  client_socket: &LookupClient,
+  data: &[u8],
+  debug: bool,
+) -> Result<Vec<u8>, Error> {
+  let request: DnsPacket = match DnsPacket::from_bytes(data) {
+    Ok(request) => request,
+    Err(e) => {
+      if debug {
+        println!("[DEBUG]: Malformed TCP query: {}", e);
+      }
+      return encode_tcp_response(&mut format_error(data));
+    },
+  };
+
+  let mut response: DnsPacket =
+    resolve_query(config, cache, client_socket, &request, debug).await?;
+  encode_tcp_response(&mut response)
+}
+
+/// FORMERR answer to a query that could not be parsed, with the ID it was sent with
+fn format_error(data: &[u8]) -> DnsPacket {
+  let mut response: DnsPacket = DnsPacket::new();
+  if let [high, low, ..] = data {
+    response.header.id = u16::from_be_bytes([*high, *low]);
+  }
+  response.header.response = true;
+  response.header.rescode = ResultCode::FormError;
+  response
+}
+
+// * >>> *
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    enums::{DnsRecord, QueryClass, QueryType},
+    scalable_block,
+    structs::{
+      BytePacketBuffer as Buffer,
+      DnsQuestion,
+      EdnsOpt,
+      EdnsOption,
+      LookAtRecord,
+      PACKET_BUFFER_SIZE,
+      TCP_MESSAGE_SIZE,
+    },
+  };
+  use std::net::Ipv4Addr;
+
+  async fn answer(data: &[u8]) -> DnsPacket {
+    let mut config: DnsServerConfig =
+      scalable_block!({ DnsServerConfig::new(Vec::new(), 0x01, 0x01) }, {
+        DnsServerConfig::new(Vec::new())
+      });
+    config.look_at(LookAtRecord::new(
+      vec!["host.test".to_string()],
+      vec![Ipv4Addr::new(192, 0, 2, 1)],
+      Vec::new(),
+    ));
+    let cache: DnsCache = DnsCache::new(0x10);
+    let client: LookupClient = LookupClient::new(Duration::from_secs(1)).unwrap();
+
+    let frame: Vec<u8> = answer_frame(&config, &cache, &client, data, false)
+      .await
+      .unwrap();
+    assert_eq!(u16::from_be_bytes([frame[0], frame[1]]) as usize, frame.len() - 0x02);
+    DnsPacket::from_bytes(&frame[0x02..]).unwrap()
+  }
+
+  // *
+
+  #[tokio::test]
+  async fn queries_larger_than_a_datagram_are_answered() {
+    let mut request: DnsPacket = DnsPacket::new();
+    request.header.id = 0x1234;
+    request.questions.push(DnsQuestion::new(
+      QueryType::A,
+      QueryClass::IN,
+      "host.test".to_string(),
+    ));
+    let mut opt: EdnsOpt = EdnsOpt::new(PACKET_BUFFER_SIZE as u16);
+    opt.options.push(EdnsOption::new(0x0C, vec![0x00; 0x0800]));
+    request.edns = Some(opt);
+
+    let mut buffer: Buffer = Buffer::growable(TCP_MESSAGE_SIZE);
+    request.write(&mut buffer).unwrap();
+    assert!(buffer.pos() > PACKET_BUFFER_SIZE);
+
+    let response: DnsPacket = answer(&buffer.buffer[..buffer.pos()]).await;
+    assert_eq!(response.header.id, 0x1234);
+    assert_eq!(response.header.rescode, ResultCode::NoError);
+    assert!(matches!(
+      response.answers.as_slice(),
+      [DnsRecord::A { address, .. }] if *address == Ipv4Addr::new(192, 0, 2, 1)
+    ));
+  }
+
+  #[tokio::test]
+  async fn malformed_queries_get_a_format_error() {
+    // the header announces a question that is missing
+    let data: [u8; 12] = [0xBE, 0xEF, 0x01, 0x00, 0x00, 0x01, 0, 0, 0, 0, 0, 0];
+    let response: DnsPacket = answer(&data).await;
+    assert_eq!(response.header.id, 0xBEEF);
+    assert!(response.header.response);
+    assert_eq!(response.header.rescode, ResultCode::FormError);
+
+    let response: DnsPacket = answer(&[0xBE]).await;
+    assert_eq!(response.header.rescode, ResultCode::FormError);
+  }
+}
@@ -0,0 +1,323 @@
+use super::lookup;
+use crate::{
+  enums::{BytePacketError as Error, DnsRecord, QueryClass, QueryType, ResultCode},
+  legacy_code,
+  structs::{DnsPacket, LookupClient, MAX_CACHE_TTL},
+  utils::{MAX_CNAME_CHAIN, cname_target, in_zone},
+};
+use std::net::{IpAddr, SocketAddr};
+
+// * >>> *
+
+/// Maximum number of upstream queries spent on a single resolution
+pub const MAX_ITERATIONS: usize = 0x40;
+const MAX_NS_DEPTH: usize = 0x04;
+
+/// Where the iteration starts: names in `zone` start at `servers`, others at the roots
+struct Hints<'a> {
+  root_servers: &'a [SocketAddr],
+  servers: &'a [SocketAddr],
+  zone: &'a str,
+  dnssec_ok: bool,
+}
+
+impl<'a> Hints<'a> {
+  fn origin(&self, qname: &str) -> (&'a [SocketAddr], &'a str) {
+    if in_zone(qname, self.zone) {
+      (self.servers, self.zone)
+    } else {
+      (self.root_servers, "")
+    }
+  }
+}
+
+// *
+
+pub async fn iterative_lookup(
+  client: &LookupClient,
+  root_servers: &[SocketAddr],
+  qname: &str,
+  qtype: QueryType,
+  qclass: QueryClass,
+  dnssec_ok: bool,
+) -> Result<DnsPacket, Error> {
+  iterative_lookup_from(
+    client,
+    root_servers,
+    "",
+    root_servers,
+    qname,
+    qtype,
+    qclass,
+    dnssec_ok,
+  )
+  .await
+}
+
+/// Resolves iteratively, starting at the given servers for names inside `zone`
+#[allow(clippy::too_many_arguments)]
+pub async fn iterative_lookup_from(
+  client: &LookupClient,
+  root_servers: &[SocketAddr],
+  zone: &str,
+  servers: &[SocketAddr],
+  qname: &str,
+  qtype: QueryType,
+  qclass: QueryClass,
+  dnssec_ok: bool,
+) -> Result<DnsPacket, Error> {
+  let hints: Hints = Hints {
+    root_servers,
+    servers,
+    zone,
+    dnssec_ok,
+  };
+  let mut budget: usize = MAX_ITERATIONS;
+  resolve(client, &hints, qname, qtype, qclass, &mut budget, 0x00).await
+}
+
+// *
+
+async fn resolve(
+  client: &LookupClient,
+  hints: &Hints<'_>,
+  qname: &str,
+  qtype: QueryType,
+  qclass: QueryClass,
+  budget: &mut usize,
+  depth: usize,
+) -> Result<DnsPacket, Error> {
+  let mut name: String = qname.to_string();
+  let mut chain: Vec<DnsRecord> = Vec::new();
+
+  for _ in 0..=MAX_CNAME_CHAIN {
+    let mut response: DnsPacket =
+      resolve_name(client, hints, &name, qtype, qclass, budget, depth).await?;
+
+    // CNAMEs pointing outside the answering zone are chased from the start again
+    let target: Option<String> = match qtype {
+      QueryType::CNAME => None,
+      _ if response.header.rescode != ResultCode::NoError => None,
+      _ => cname_target(&response.answers, &name, qtype),
+    };
+
+    chain.append(&mut response.answers);
+    match target {
+      Some(target) => name = target,
+      None => {
+        response.answers = chain;
+        return Ok(response);
+      },
+    }
+  }
+
+  Err(Error::Custom(format!("CNAME chain too long for {}", qname)))
+}
+
+// *
+
+async fn resolve_name(
+  client: &LookupClient,
+  hints: &Hints<'_>,
+  qname: &str,
+  qtype: QueryType,
+  qclass: QueryClass,
+  budget: &mut usize,
+  depth: usize,
+) -> Result<DnsPacket, Error> {
+  let (start_servers, start_zone) = hints.origin(qname);
+
+  // a cached delegation closer to the name skips the referrals leading to it
+  let (mut servers, mut zone, mut cached): (Vec<SocketAddr>, String, bool) =
+    match client.delegations.closest(qname, start_zone) {
+      Some((zone, servers)) => (servers, zone, true),
+      None => (start_servers.to_vec(), start_zone.to_string(), false),
+    };
+
+  loop {
+    let mut response: DnsPacket =
+      match query_servers(client, hints, &servers, qname, qtype, qclass, budget).await {
+        Ok(response) => response,
+        // servers of a cached delegation that fail are forgotten, and the name is
+        // resolved from the start again
+        Err(_) if cached => {
+          client.delegations.remove(&zone);
+          servers = start_servers.to_vec();
+          zone = start_zone.to_string();
+          cached = false;
+          continue;
+        },
+        Err(e) => return Err(e),
+      };
+
+    // records outside the zone the server is authoritative for are not trusted
+    response.answers.retain(|record| in_zone(record.domain(), &zone));
+    response.authorities.retain(|record| in_zone(record.domain(), &zone));
+    response.resources.retain(|record| in_zone(record.domain(), &zone));
+
+    if response.header.rescode != ResultCode::NoError || !response.answers.is_empty() {
+      return Ok(response);
+    }
+
+    // a referral must move strictly closer to the queried name
+    let Some(child) = response
+      .authorities
+      .iter()
+      .filter_map(|record| match record {
+        DnsRecord::NS { domain, .. } if domain.len() > zone.len() => Some(domain),
+        _ => None,
+      })
+      .filter(|domain| in_zone(qname, domain))
+      .max_by_key(|domain| domain.len())
+      .cloned()
+    else {
+      // a referral that makes no progress comes from a lame delegation
+      let lame: bool = response.authorities.iter().any(|record| {
+        matches!(record, DnsRecord::NS { .. })
+      }) && !response.authorities.iter().any(|record| {
+        matches!(record, DnsRecord::SOA { .. })
+      });
+
+      if lame {
+        return Err(Error::Custom(format!("Lame delegation for {}", qname)));
+      }
+      return Ok(response);
+    };
+
+    let mut ttl: u32 = MAX_CACHE_TTL;
+    let hosts: Vec<&str> = response
+      .authorities
+      .iter()
+      .filter_map(|record| match record {
+        DnsRecord::NS {
+          domain, host, ttl: ns_ttl, ..
+        } if *domain == child => {
+          ttl = ttl.min(*ns_ttl);
+          Some(host.as_str())
+        },
+        _ => None,
+      })
+      .collect();
+
+    // the delegation is cached no longer than the NS records and the glue used
+    let mut addresses: Vec<IpAddr> = response
+      .resources
+      .iter()
+      .filter_map(|record| match record {
+        DnsRecord::A {
+          address, domain, ttl: glue_ttl, ..
+        } if hosts.contains(&domain.as_str()) => {
+          ttl = ttl.min(*glue_ttl);
+          Some(IpAddr::V4(*address))
+        },
+        DnsRecord::AAAA {
+          address, domain, ttl: glue_ttl, ..
+        } if hosts.contains(&domain.as_str()) => {
+          ttl = ttl.min(*glue_ttl);
+          Some(IpAddr::V6(*address))
+        },
+        _ => None,
+      })
+      .collect();
+
+    if addresses.is_empty() {
+      if depth >= MAX_NS_DEPTH {
+        return Err(Error::Custom(format!("Delegation too deep for {}", qname)));
+      }
+
+      for host in hosts {
+        addresses = resolve_ns(client, hints, host, budget, depth).await;
+        if !addresses.is_empty() {
+          break;
+        }
+      }
+    }
+
+    if addresses.is_empty() {
+      return Err(legacy_code!(
+        { Error::Custom(format!("No reachable nameserver for {}", child)) },
+        { Error::LookupFailed }
+      ));
+    }
+
+    servers = addresses
+      .into_iter()
+      .map(|address| SocketAddr::new(address, 53))
+      .collect();
+    client.delegations.insert(&child, &servers, ttl);
+    zone = child;
+    cached = false;
+  }
+}
+
+// *
+
+async fn resolve_ns(
+  client: &LookupClient,
+  hints: &Hints<'_>,
+  host: &str,
+  budget: &mut usize,
+  depth: usize,
+) -> Vec<IpAddr> {
+  let mut addresses: Vec<IpAddr> = Vec::new();
+  for qtype in [QueryType::A, QueryType::AAAA] {
+    let resolved: Result<DnsPacket, Error> = Box::pin(resolve(
+      client,
+      hints,
+      host,
+      qtype,
+      QueryClass::IN,
+      budget,
+      depth + 0x01,
+    ))
+    .await;
+
+    if let Ok(packet) = resolved {
+      addresses.extend(packet.answers.iter().filter_map(|record| match record {
+        DnsRecord::A { address, .. } => Some(IpAddr::V4(*address)),
+        DnsRecord::AAAA { address, .. } => Some(IpAddr::V6(*address)),
+        _ => None,
+      }));
+    }
+
+    if !addresses.is_empty() {
+      break;
+    }
+  }
+  addresses
+}
+
+// *
+
+async fn query_servers(
+  client: &LookupClient,
+  hints: &Hints<'_>,
+  servers: &[SocketAddr],
+  qname: &str,
+  qtype: QueryType,
+  qclass: QueryClass,
+  budget: &mut usize,
+) -> Result<DnsPacket, Error> {
+  for server in client.health.order(servers) {
+    if *budget == 0x00 {
+      return Err(Error::Custom(format!("Iteration limit reached for {}", qname)));
+    }
+    *budget -= 0x01;
+
+    // iterative queries ask the authoritative servers directly (RD bit unset)
+    if let Ok(response) =
+      lookup(client, qname, qtype, qclass, server, false, hints.dnssec_ok).await
+      && !matches!(
+        response.header.rescode,
+        ResultCode::ServerFail | ResultCode::Refused
+      )
+    {
+      return Ok(response);
+    }
+  }
+
+  Err(legacy_code!(
+    { Error::Custom("All DNS servers failed".to_string()) },
+    { Error::LookupFailed }
+  ))
+}
@@ -1,31 +1,331 @@
 use crate::{
-  enums::{BytePacketError as Error, QueryType},
-  structs::{BytePacketBuffer as Buffer, DnsPacket, DnsQuestion},
+  enums::{BytePacketError as Error, QueryClass, QueryType, ResultCode},
+  structs::{
+    BytePacketBuffer as Buffer,
+    DnsPacket,
+    DnsQuestion,
+    EDNS_DNSSEC_OK,
+    EdnsOpt,
+    LookupClient,
+    PACKET_BUFFER_SIZE,
+  },
+};
+use std::{
+  io::{Error as IoError, ErrorKind},
+  net::SocketAddr,
+};
+use tokio::{
+  io::{AsyncReadExt, AsyncWriteExt},
+  net::{TcpStream, UdpSocket},
+  time::{Instant, timeout, timeout_at},
 };
-use std::net::{SocketAddr, UdpSocket};
 
 // * >>> *
 
-pub fn lookup(
-  socket: &UdpSocket,
+pub async fn lookup(
+  client: &LookupClient,
   qname: &str,
   qtype: QueryType,
+  qclass: QueryClass,
   server: SocketAddr,
+  recursion_desired: bool,
+  dnssec_ok: bool,
 ) -> Result<DnsPacket, Error> {
+  let question: DnsQuestion = DnsQuestion::new(qtype, qclass, qname.to_string());
   let mut packet: DnsPacket = DnsPacket::new();
-  packet.header.id = 0x29A; // random ID
+  packet.header.id = rand::random::<u16>();
   packet.header.questions = 0x01;
-  packet.header.recursion_desired = true;
-  packet
-    .questions
-    .push(DnsQuestion::new(qtype, qname.to_string()));
+  packet.header.recursion_desired = recursion_desired;
+  packet.questions.push(question.clone());
+  let mut opt: EdnsOpt = EdnsOpt::new(PACKET_BUFFER_SIZE as u16);
+  if dnssec_ok {
+    opt.flags = EDNS_DNSSEC_OK;
+  }
+  packet.edns = Some(opt);
 
   let mut req_buffer: Buffer = Buffer::new();
   packet.write(&mut req_buffer)?;
-  socket.send_to(&req_buffer.buffer[0..req_buffer.position], server)?;
+  let request: &[u8] = &req_buffer.buffer[0..req_buffer.position];
+
+  // every exchange feeds the health of the server used to pick upstreams
+  let started: Instant = Instant::now();
+  let response: DnsPacket =
+    match exchange(client, request, packet.header.id, &question, server).await {
+      Ok(response) => response,
+      Err(e) => {
+        client.health.record_failure(server);
+        return Err(e);
+      },
+    };
+
+  // a truncated reply is asked again over TCP (RFC 7766 section 5), and is only
+  // returned as is, with the TC bit, when that fails
+  let response: DnsPacket = match response.header.truncated_message {
+    true => exchange_tcp(client, request, packet.header.id, &question, server)
+      .await
+      .unwrap_or(response),
+    false => response,
+  };
+
+  match response.header.rescode {
+    ResultCode::ServerFail | ResultCode::Refused => client.health.record_failure(server),
+    _ => client.health.record_success(server, started.elapsed()),
+  }
+  Ok(response)
+}
+
+// *
+
+async fn exchange(
+  client: &LookupClient,
+  request: &[u8],
+  id: u16,
+  question: &DnsQuestion,
+  server: SocketAddr,
+) -> Result<DnsPacket, Error> {
+  let socket: UdpSocket = client.socket(&server).await?;
+  socket.send_to(request, server).await?;
+
+  // datagrams that do not answer this exact query are dropped until the deadline
+  let deadline: Instant = Instant::now() + client.timeout;
+  loop {
+    let mut res_buffer: Buffer = Buffer::new();
+    let Ok(received) =
+      timeout_at(deadline, socket.recv_from(&mut res_buffer.buffer)).await
+    else {
+      return Err(Error::IoError(IoError::new(
+        ErrorKind::TimedOut,
+        format!("No valid response from {}", server),
+      )));
+    };
+
+    let (_, src) = received?;
+    if src != server {
+      continue;
+    }
+
+    let Ok(response) = DnsPacket::from_buffer(&mut res_buffer) else {
+      continue;
+    };
+
+    if response.header.id == id
+      && response.header.response
+      && response.questions.len() == 0x01
+      && matches_question(&response.questions[0], question)
+    {
+      return Ok(response);
+    }
+  }
+}
+
+async fn exchange_tcp(
+  client: &LookupClient,
+  request: &[u8],
+  id: u16,
+  question: &DnsQuestion,
+  server: SocketAddr,
+) -> Result<DnsPacket, Error> {
+  let timed_out = |_| {
+    Error::IoError(IoError::new(
+      ErrorKind::TimedOut,
+      format!("No TCP response from {}", server),
+    ))
+  };
+
+  let response: Vec<u8> = timeout(client.timeout, async {
+    let mut stream: TcpStream = TcpStream::connect(server).await?;
+    let mut frame: Vec<u8> = Vec::with_capacity(request.len() + 0x02);
+    frame.extend_from_slice(&(request.len() as u16).to_be_bytes());
+    frame.extend_from_slice(request);
+    stream.write_all(&frame).await?;
+
+    let mut len_buffer: [u8; 2] = [0x00; 2];
+    stream.read_exact(&mut len_buffer).await?;
+    let mut response: Vec<u8> = vec![0x00; u16::from_be_bytes(len_buffer) as usize];
+    stream.read_exact(&mut response).await?;
+    Ok::<Vec<u8>, IoError>(response)
+  })
+  .await
+  .map_err(timed_out)??;
+
+  let response: DnsPacket = DnsPacket::from_bytes(&response)?;
+  if response.header.id == id
+    && response.header.response
+    && response.questions.len() == 0x01
+    && matches_question(&response.questions[0], question)
+  {
+    return Ok(response);
+  }
+
+  Err(Error::IoError(IoError::new(
+    ErrorKind::InvalidData,
+    format!("Invalid TCP response from {}", server),
+  )))
+}
+
+// *
+
+fn matches_question(received: &DnsQuestion, sent: &DnsQuestion) -> bool {
+  received.qtype == sent.qtype
+    && received.qclass == sent.qclass
+    && received.name.eq_ignore_ascii_case(&sent.name)
+}
+
+// * >>> *
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{enums::DnsRecord, structs::TCP_MESSAGE_SIZE};
+  use std::{
+    io::{Read, Write},
+    net::{Ipv4Addr, TcpListener, UdpSocket as StdUdpSocket},
+    thread::{self, JoinHandle},
+    time::Duration,
+  };
+
+  /// Answers the first query it gets with `reply`, from a socket on the loopback
+  fn upstream(
+    reply: impl FnOnce(&StdUdpSocket, DnsPacket, SocketAddr) + Send + 'static,
+  ) -> (SocketAddr, JoinHandle<()>) {
+    let socket: StdUdpSocket = StdUdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    let server: SocketAddr = socket.local_addr().unwrap();
+    let handle: JoinHandle<()> = thread::spawn(move || {
+      let mut buffer: Buffer = Buffer::new();
+      let (_, src) = socket.recv_from(&mut buffer.buffer).unwrap();
+      let query: DnsPacket = DnsPacket::from_buffer(&mut buffer).unwrap();
+      reply(&socket, query, src);
+    });
+    (server, handle)
+  }
+
+  /// Answers the first query it gets over TCP with `reply`, on the same port
+  fn upstream_tcp(
+    server: SocketAddr,
+    reply: impl FnOnce(DnsPacket) -> DnsPacket + Send + 'static,
+  ) -> JoinHandle<()> {
+    let listener: TcpListener = TcpListener::bind(server).unwrap();
+    thread::spawn(move || {
+      let (mut stream, _) = listener.accept().unwrap();
+      let mut len_buffer: [u8; 2] = [0x00; 2];
+      stream.read_exact(&mut len_buffer).unwrap();
+      let mut data: Vec<u8> = vec![0x00; u16::from_be_bytes(len_buffer) as usize];
+      stream.read_exact(&mut data).unwrap();
+
+      let mut response: DnsPacket = reply(DnsPacket::from_bytes(&data).unwrap());
+      let mut buffer: Buffer = Buffer::growable(TCP_MESSAGE_SIZE);
+      response.write(&mut buffer).unwrap();
+      stream.write_all(&(buffer.pos() as u16).to_be_bytes()).unwrap();
+      stream.write_all(&buffer.buffer[..buffer.pos()]).unwrap();
+    })
+  }
+
+  fn truncated(query: &DnsPacket) -> DnsPacket {
+    let mut packet: DnsPacket = answer(query, 0x01);
+    packet.header.truncated_message = true;
+    packet
+  }
+
+  fn answer(query: &DnsPacket, last_octet: u8) -> DnsPacket {
+    let mut packet: DnsPacket = query.clone();
+    packet.header.response = true;
+    packet.answers.push(DnsRecord::A {
+      domain: query.questions[0].name.clone(),
+      class: QueryClass::IN,
+      address: Ipv4Addr::new(0x0A, 0x00, 0x00, last_octet),
+      ttl: 300,
+    });
+    packet
+  }
+
+  fn send(socket: &StdUdpSocket, mut packet: DnsPacket, dst: SocketAddr) {
+    let mut buffer: Buffer = Buffer::new();
+    packet.write(&mut buffer).unwrap();
+    socket.send_to(&buffer.buffer[..buffer.pos()], dst).unwrap();
+  }
+
+  async fn query(timeout_ms: u64, server: SocketAddr) -> Result<DnsPacket, Error> {
+    let client: LookupClient =
+      LookupClient::new(Duration::from_millis(timeout_ms)).unwrap();
+    lookup(&client, "example.com", QueryType::A, QueryClass::IN, server, true, false)
+      .await
+  }
+
+  // *
+
+  #[tokio::test]
+  async fn replies_that_do_not_answer_the_query_are_dropped() {
+    let (server, handle) = upstream(|socket, query, src| {
+      let mut wrong_id: DnsPacket = answer(&query, 0x02);
+      wrong_id.header.id = query.header.id.wrapping_add(0x01);
+      send(socket, wrong_id, src);
+
+      let mut not_response: DnsPacket = answer(&query, 0x03);
+      not_response.header.response = false;
+      send(socket, not_response, src);
+
+      let mut wrong_name: DnsPacket = answer(&query, 0x04);
+      wrong_name.questions[0].name = "example.org".to_string();
+      send(socket, wrong_name, src);
+
+      let mut wrong_type: DnsPacket = answer(&query, 0x05);
+      wrong_type.questions[0].qtype = QueryType::AAAA;
+      send(socket, wrong_type, src);
+
+      let other: StdUdpSocket = StdUdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+      send(&other, answer(&query, 0x06), src);
+
+      // the question name is matched without regard to case
+      let mut valid: DnsPacket = answer(&query, 0x01);
+      valid.questions[0].name = "EXAMPLE.com".to_string();
+      send(socket, valid, src);
+    });
+
+    let response: DnsPacket = query(0x7D0, server).await.unwrap();
+    handle.join().unwrap();
+    assert_eq!(response.get_random_a(), Some(Ipv4Addr::new(0x0A, 0x00, 0x00, 0x01)));
+  }
+
+  #[tokio::test]
+  async fn lookup_times_out_without_a_valid_reply() {
+    let (server, handle) = upstream(|socket, query, src| {
+      let mut wrong_id: DnsPacket = answer(&query, 0x02);
+      wrong_id.header.id = query.header.id.wrapping_add(0x01);
+      send(socket, wrong_id, src);
+    });
+
+    let result: Result<DnsPacket, Error> = query(0xC8, server).await;
+    handle.join().unwrap();
+    assert!(result.is_err());
+  }
+
+  #[tokio::test]
+  async fn truncated_replies_are_asked_again_over_tcp() {
+    let (server, handle) = upstream(|socket, query, src| {
+      send(socket, truncated(&query), src);
+    });
+    let tcp_handle: JoinHandle<()> = upstream_tcp(server, |query| {
+      let mut packet: DnsPacket = answer(&query, 0x01);
+      packet.answers.append(&mut answer(&query, 0x02).answers);
+      packet
+    });
+
+    let response: DnsPacket = query(0x7D0, server).await.unwrap();
+    handle.join().unwrap();
+    tcp_handle.join().unwrap();
+    assert!(!response.header.truncated_message);
+    assert_eq!(response.answers.len(), 0x02);
+  }
 
-  let mut res_buffer: Buffer = Buffer::new();
-  socket.recv_from(&mut res_buffer.buffer)?;
+  #[tokio::test]
+  async fn truncated_reply_is_kept_when_tcp_fails() {
+    let (server, handle) = upstream(|socket, query, src| {
+      send(socket, truncated(&query), src);
+    });
 
-  DnsPacket::from_buffer(&mut res_buffer)
+    let response: DnsPacket = query(0x7D0, server).await.unwrap();
+    handle.join().unwrap();
+    assert!(response.header.truncated_message);
+    assert_eq!(response.answers.len(), 0x01);
+  }
 }
@@ -1,11 +1,29 @@
+mod build_block_packet;
+mod build_chaos_packet;
+mod build_cname_packet;
 mod build_dns_packet;
+mod build_ptr_packet;
+mod build_srv_packet;
+mod build_zone_packet;
 mod handle_query;
+mod handle_tcp_connection;
+mod iterative_lookup;
 mod lookup;
 mod recursive_lookup;
+mod resolve_query;
 
 // * >>> *
 
+pub use build_block_packet::build_block_packet;
+pub use build_chaos_packet::build_chaos_packet;
+pub use build_cname_packet::build_cname_packet;
 pub use build_dns_packet::build_dns_packet;
+pub use build_ptr_packet::build_ptr_packet;
+pub use build_srv_packet::build_srv_packet;
+pub use build_zone_packet::build_zone_packet;
 pub use handle_query::handle_query;
+pub use handle_tcp_connection::{MAX_TCP_PIPELINE, handle_tcp_connection};
+pub use iterative_lookup::{MAX_ITERATIONS, iterative_lookup, iterative_lookup_from};
 pub use lookup::lookup;
 pub use recursive_lookup::recursive_lookup;
+pub use resolve_query::resolve_query;
@@ -1,63 +1,153 @@
 use super::lookup;
 use crate::{
-  enums::{BytePacketError as Error, QueryType, ResultCode},
+  enums::{BytePacketError as Error, DnsRecord, QueryClass, QueryType, ResultCode},
   legacy_code,
-  structs::DnsPacket,
+  structs::{DnsPacket, LookupClient},
 };
-use std::net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket};
+use std::net::SocketAddr;
 
 // * >>> *
 
-pub fn recursive_lookup(
-  socket: &UdpSocket,
-  mut dns_servers: Vec<Ipv4Addr>,
+pub async fn recursive_lookup(
+  socket: &LookupClient,
+  dns_servers: Vec<SocketAddr>,
   qname: &str,
   qtype: QueryType,
+  qclass: QueryClass,
+  dnssec_ok: bool,
 ) -> Result<DnsPacket, Error> {
-  while let Some(current_ns) = dns_servers.pop() {
-    let mut ns_ip: Ipv4Addr = current_ns;
-
-    loop {
-      let server: SocketAddr = SocketAddr::new(IpAddr::V4(ns_ip), 53);
-      let response: DnsPacket = lookup(socket, qname, qtype, server)?;
-
-      if (!response.answers.is_empty() && response.header.rescode == ResultCode::NoError)
-        || response.header.rescode == ResultCode::NxDomain
-      {
-        return Ok(response);
-      }
-
-      if let Some(new_ns_ip) = response.get_resolved_ns(qname) {
-        ns_ip = new_ns_ip;
-        continue;
-      }
-
-      let new_ns_name: &str = match response.get_unresolved_ns(qname) {
-        Some(name) => name,
-        None => return Ok(response),
-      };
+  // servers are tried fastest first, the next one is used when a server fails
+  for current_ns in socket.health.order(&dns_servers) {
+    let response: Result<DnsPacket, Error> =
+      lookup(socket, qname, qtype, qclass, current_ns, true, dnssec_ok).await;
 
-      let recursive_response: DnsPacket = recursive_lookup(
-        socket,
-        vec![
-          Ipv4Addr::from([198, 41, 0, 4]), // a.root-servers.net
-          Ipv4Addr::from([1, 1, 1, 1]),    // one.one.one.one (cloudflare)
-          Ipv4Addr::from([1, 0, 0, 1]),    // one.one.one.one (cloudflare)
-        ],
-        new_ns_name,
-        QueryType::A,
-      )?;
-
-      if let Some(new_ip) = recursive_response.get_random_a() {
-        ns_ip = new_ip;
-      } else {
-        return Ok(response);
-      }
+    // the forwarder's answer is returned as it is, referrals are not followed
+    if let Ok(response) = response
+      && !matches!(
+        response.header.rescode,
+        ResultCode::ServerFail | ResultCode::Refused
+      )
+      && !is_referral(&response)
+    {
+      return Ok(response);
     }
   }
 
   Err(legacy_code!(
-    { Error::Custom(format!("All DNS servers failed")) },
+    { Error::Custom("All DNS servers failed".to_string()) },
     { Error::LookupFailed }
   ))
 }
+
+// *
+
+fn is_referral(response: &DnsPacket) -> bool {
+  // a forwarder that does not recurse hands back NS records instead of an answer
+  response.header.rescode == ResultCode::NoError
+    && response.answers.is_empty()
+    && response
+      .authorities
+      .iter()
+      .any(|record| matches!(record, DnsRecord::NS { .. }))
+    && !response
+      .authorities
+      .iter()
+      .any(|record| matches!(record, DnsRecord::SOA { .. }))
+}
+
+// * >>> *
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::structs::BytePacketBuffer as Buffer;
+  use std::{
+    net::{Ipv4Addr, UdpSocket as StdUdpSocket},
+    thread::{self, JoinHandle},
+    time::Duration,
+  };
+
+  /// Answers the first query it gets, if any, with `reply`
+  fn upstream(reply: fn(DnsPacket) -> DnsPacket) -> (SocketAddr, JoinHandle<()>) {
+    let socket: StdUdpSocket = StdUdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    socket.set_read_timeout(Some(Duration::from_secs(0x01))).unwrap();
+    let server: SocketAddr = socket.local_addr().unwrap();
+    let handle: JoinHandle<()> = thread::spawn(move || {
+      let mut buffer: Buffer = Buffer::new();
+      let Ok((_, src)) = socket.recv_from(&mut buffer.buffer) else {
+        return;
+      };
+      let mut response: DnsPacket = reply(DnsPacket::from_buffer(&mut buffer).unwrap());
+      response.header.response = true;
+
+      let mut buffer: Buffer = Buffer::new();
+      response.write(&mut buffer).unwrap();
+      socket.send_to(&buffer.buffer[..buffer.pos()], src).unwrap();
+    });
+    (server, handle)
+  }
+
+  fn referral(mut query: DnsPacket) -> DnsPacket {
+    query.authorities.push(DnsRecord::NS {
+      domain: "com".to_string(),
+      class: QueryClass::IN,
+      host: "a.gtld-servers.net".to_string(),
+      ttl: 300,
+    });
+    query.resources.push(DnsRecord::A {
+      domain: "a.gtld-servers.net".to_string(),
+      class: QueryClass::IN,
+      address: Ipv4Addr::LOCALHOST,
+      ttl: 300,
+    });
+    query
+  }
+
+  fn no_data(mut query: DnsPacket) -> DnsPacket {
+    query.authorities.push(DnsRecord::SOA {
+      domain: "example.com".to_string(),
+      class: QueryClass::IN,
+      mname: "ns.example.com".to_string(),
+      rname: "hostmaster.example.com".to_string(),
+      serial: 0x01,
+      refresh: 3600,
+      retry: 600,
+      expire: 86400,
+      minimum: 300,
+      ttl: 300,
+    });
+    query
+  }
+
+  async fn query(servers: Vec<SocketAddr>) -> Result<DnsPacket, Error> {
+    let client: LookupClient = LookupClient::new(Duration::from_millis(0x1F4)).unwrap();
+    recursive_lookup(&client, servers, "example.com", QueryType::A, QueryClass::IN, false)
+      .await
+  }
+
+  // *
+
+  #[tokio::test]
+  async fn forwarder_answers_are_returned_as_they_are() {
+    let (server, handle) = upstream(no_data);
+
+    let response: DnsPacket = query(vec![server]).await.unwrap();
+    handle.join().unwrap();
+
+    assert!(response.answers.is_empty());
+    assert!(matches!(response.authorities[0], DnsRecord::SOA { .. }));
+  }
+
+  #[tokio::test]
+  async fn referrals_fail_over_to_the_next_forwarder() {
+    let (referring, referring_handle) = upstream(referral);
+    let (answering, answering_handle) = upstream(no_data);
+
+    let response: DnsPacket = query(vec![referring, answering]).await.unwrap();
+    referring_handle.join().unwrap();
+    answering_handle.join().unwrap();
+
+    assert!(matches!(response.authorities[0], DnsRecord::SOA { .. }));
+    assert!(query(vec![upstream(referral).0]).await.is_err());
+  }
+}
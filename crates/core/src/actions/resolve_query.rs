@@ -0,0 +1,350 @@
+use crate::{
+  actions::{iterative_lookup, iterative_lookup_from, recursive_lookup},
+  enums::{BytePacketError as Error, QueryType, ResolveMode, ResultCode},
+  structs::{
+    DnsCache,
+    DnsPacket,
+    DnsQuestion,
+    DnsServerConfig,
+    EDNS_BAD_VERSION,
+    EdnsOpt,
+    LookupClient,
+  },
+  utils::*,
+};
+use std::net::SocketAddr;
+
+// * >>> *
+
+pub async fn resolve_query(
+  config: &DnsServerConfig,
+  cache: &DnsCache,
+  client_socket: &LookupClient,
+  request: &DnsPacket,
+  debug: bool,
+) -> Result<DnsPacket, Error> {
+  let mut response: DnsPacket = DnsPacket::new();
+  response.header.id = request.header.id;
+  response.header.recursion_desired = true;
+  response.header.recursion_available = true;
+  response.header.response = true;
+
+  // only EDNS version 0 is supported (RFC 6891 section 6.1.3)
+  if let Some(edns) = &request.edns
+    && edns.version > 0
+  {
+    let mut opt: EdnsOpt = EdnsOpt::reply(edns);
+    opt.extended_rcode = EDNS_BAD_VERSION;
+    response.edns = Some(opt);
+    return Ok(response);
+  }
+
+  let mut response: DnsPacket =
+    answer_query(config, cache, client_socket, request, response, debug).await;
+
+  // the upstream OPT record only carries its extended RCODE over to the reply
+  let upstream: Option<EdnsOpt> = response.edns.take();
+  if let Some(edns) = &request.edns {
+    let mut opt: EdnsOpt = EdnsOpt::reply(edns);
+    opt.extended_rcode = upstream.map_or(0x00, |upstream| upstream.extended_rcode);
+    response.edns = Some(opt);
+  }
+  Ok(response)
+}
+
+// *
+
+async fn answer_query(
+  config: &DnsServerConfig,
+  cache: &DnsCache,
+  client_socket: &LookupClient,
+  request: &DnsPacket,
+  mut response: DnsPacket,
+  debug: bool,
+) -> DnsPacket {
+  let Some(question) = request.questions.first() else {
+    response.header.rescode = ResultCode::FormError;
+    return response;
+  };
+  let dnssec_ok: bool = request.edns.as_ref().is_some_and(EdnsOpt::dnssec_ok);
+
+  let mut response: DnsPacket =
+    match handle_look_at(config, question, &mut response, debug) {
+      Some(result_packet) => result_packet,
+      None => {
+        match lookup_cached(config, cache, client_socket, question, dnssec_ok, debug)
+          .await
+        {
+          Ok(mut result) => {
+            response.questions.push(question.clone());
+            response.header.rescode = result.header.rescode;
+            response.header.truncated_message = result.header.truncated_message;
+            response.answers.append(&mut result.answers);
+            response.authorities.append(&mut result.authorities);
+            response.resources.append(&mut result.resources);
+            response.edns = result.edns;
+          },
+          Err(_) => {
+            response.header.rescode = ResultCode::ServerFail;
+          },
+        }
+        response
+      },
+    };
+
+  chase_cnames(config, cache, client_socket, question, &mut response, dnssec_ok, debug)
+    .await;
+  response
+}
+
+// *
+
+async fn lookup_cached(
+  config: &DnsServerConfig,
+  cache: &DnsCache,
+  client_socket: &LookupClient,
+  question: &DnsQuestion,
+  dnssec_ok: bool,
+  debug: bool,
+) -> Result<DnsPacket, Error> {
+  match cache.get(question, dnssec_ok) {
+    Some(cached) => {
+      if debug {
+        println!("[DEBUG]: Cache hit: {} ({:?})", question.name, question.qtype);
+      }
+      Ok(cached)
+    },
+    // a truncated answer is missing records, it is never served from the cache
+    None => upstream_lookup(config, client_socket, question, dnssec_ok)
+      .await
+      .inspect(|result| {
+        if !result.header.truncated_message {
+          cache.insert(question, dnssec_ok, result);
+        }
+      }),
+  }
+}
+
+// *
+
+/// Follows the CNAME chain an answer stops at, locally or upstream, until the records
+/// of the queried type are found
+async fn chase_cnames(
+  config: &DnsServerConfig,
+  cache: &DnsCache,
+  client_socket: &LookupClient,
+  question: &DnsQuestion,
+  response: &mut DnsPacket,
+  dnssec_ok: bool,
+  debug: bool,
+) {
+  if matches!(question.qtype, QueryType::CNAME | QueryType::UNKNOWN(0xFF)) {
+    return;
+  }
+
+  let mut visited: Vec<String> = vec![question.name.to_lowercase()];
+  while response.header.rescode == ResultCode::NoError
+    && let Some(target) = cname_target(&response.answers, &question.name, question.qtype)
+  {
+    // chains that come back to a name already seen, or that are too long, never end
+    if visited.contains(&target.to_lowercase()) || visited.len() > MAX_CNAME_CHAIN {
+      response.header.rescode = ResultCode::ServerFail;
+      return;
+    }
+    visited.push(target.to_lowercase());
+
+    let target_question: DnsQuestion =
+      DnsQuestion::new(question.qtype, question.qclass, target);
+    let mut target_response: DnsPacket = DnsPacket::new();
+    let result: Result<DnsPacket, Error> =
+      match handle_look_at(config, &target_question, &mut target_response, debug) {
+        Some(local) => Ok(local),
+        None => {
+          lookup_cached(
+            config,
+            cache,
+            client_socket,
+            &target_question,
+            dnssec_ok,
+            debug,
+          )
+          .await
+        },
+      };
+
+    let Ok(result) = result else {
+      response.header.rescode = ResultCode::ServerFail;
+      return;
+    };
+
+    response.header.rescode = result.header.rescode;
+    response.header.truncated_message |= result.header.truncated_message;
+    response.edns = result.edns;
+    // the chain ends at a name without data of the type (NODATA or NXDOMAIN)
+    let chain_end: bool = result.answers.is_empty();
+    for record in result.answers {
+      if !response.answers.contains(&record) {
+        response.answers.push(record);
+      }
+    }
+    response.authorities = result.authorities;
+    response.resources = result.resources;
+    if chain_end {
+      break;
+    }
+  }
+}
+
+// *
+
+async fn upstream_lookup(
+  config: &DnsServerConfig,
+  client_socket: &LookupClient,
+  question: &DnsQuestion,
+  dnssec_ok: bool,
+) -> Result<DnsPacket, Error> {
+  // the most specific zone rule wins, other names use the server defaults
+  let (nameservers, mode, zone): (&[SocketAddr], ResolveMode, &str) =
+    match config.find_forward_zone(&question.name) {
+      Some(forward_zone) => (
+        &forward_zone.nameservers,
+        forward_zone.mode,
+        &forward_zone.zone,
+      ),
+      None => (&config.nameservers, config.mode, ""),
+    };
+
+  match mode {
+    ResolveMode::Forward => {
+      recursive_lookup(
+        client_socket,
+        nameservers.to_vec(),
+        &question.name,
+        question.qtype,
+        question.qclass,
+        dnssec_ok,
+      )
+      .await
+    },
+    ResolveMode::Recursive if zone.is_empty() || nameservers.is_empty() => {
+      iterative_lookup(
+        client_socket,
+        &config.root_hints,
+        &question.name,
+        question.qtype,
+        question.qclass,
+        dnssec_ok,
+      )
+      .await
+    },
+    ResolveMode::Recursive => {
+      iterative_lookup_from(
+        client_socket,
+        &config.root_hints,
+        zone,
+        nameservers,
+        &question.name,
+        question.qtype,
+        question.qclass,
+        dnssec_ok,
+      )
+      .await
+    },
+  }
+}
+
+// * >>> *
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    enums::{DnsRecord, QueryClass},
+    scalable_block,
+    structs::{BytePacketBuffer as Buffer, EDNS_DNSSEC_OK},
+  };
+  use std::{
+    net::{Ipv4Addr, UdpSocket as StdUdpSocket},
+    thread::{self, JoinHandle},
+    time::Duration,
+  };
+
+  /// Answers the first query it gets with `reply`, from a socket on the loopback
+  fn upstream(reply: fn(DnsPacket) -> DnsPacket) -> (SocketAddr, JoinHandle<()>) {
+    let socket: StdUdpSocket = StdUdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    let server: SocketAddr = socket.local_addr().unwrap();
+    let handle: JoinHandle<()> = thread::spawn(move || {
+      let mut buffer: Buffer = Buffer::new();
+      let (_, src) = socket.recv_from(&mut buffer.buffer).unwrap();
+      let mut response: DnsPacket = reply(DnsPacket::from_buffer(&mut buffer).unwrap());
+
+      let mut buffer: Buffer = Buffer::new();
+      response.write(&mut buffer).unwrap();
+      socket.send_to(&buffer.buffer[..buffer.pos()], src).unwrap();
+    });
+    (server, handle)
+  }
+
+  fn request(version: u8, flags: u16) -> DnsPacket {
+    let mut packet: DnsPacket = DnsPacket::new();
+    packet.header.id = 0x1234;
+    packet.questions.push(DnsQuestion::new(
+      QueryType::A,
+      QueryClass::IN,
+      "example.com".to_string(),
+    ));
+    let mut opt: EdnsOpt = EdnsOpt::new(0x0200);
+    opt.version = version;
+    opt.flags = flags;
+    packet.edns = Some(opt);
+    packet
+  }
+
+  async fn resolve(nameservers: Vec<SocketAddr>, request: &DnsPacket) -> DnsPacket {
+    let config: DnsServerConfig =
+      scalable_block!({ DnsServerConfig::new(nameservers, 0x01, 0x01) }, {
+        DnsServerConfig::new(nameservers)
+      });
+    let cache: DnsCache = DnsCache::new(0x10);
+    let client: LookupClient = LookupClient::new(Duration::from_secs(0x01)).unwrap();
+    resolve_query(&config, &cache, &client, request, false).await.unwrap()
+  }
+
+  // *
+
+  #[tokio::test]
+  async fn unsupported_edns_versions_get_badvers() {
+    let response: DnsPacket = resolve(Vec::new(), &request(0x01, EDNS_DNSSEC_OK)).await;
+    let opt: EdnsOpt = response.edns.unwrap();
+    assert_eq!(opt.extended_rcode, EDNS_BAD_VERSION);
+    assert!(opt.dnssec_ok());
+    assert!(response.answers.is_empty());
+  }
+
+  #[tokio::test]
+  async fn dnssec_ok_and_extended_rcode_are_passed_on() {
+    let (server, handle) = upstream(|mut query| {
+      // the DO bit of the client reaches the upstream
+      let mut opt: EdnsOpt = query.edns.take().unwrap();
+      assert!(opt.dnssec_ok());
+      opt.extended_rcode = 0x01;
+
+      query.header.response = true;
+      query.answers.push(DnsRecord::A {
+        domain: "example.com".to_string(),
+        class: QueryClass::IN,
+        address: Ipv4Addr::new(0x0A, 0x00, 0x00, 0x01),
+        ttl: 300,
+      });
+      query.edns = Some(opt);
+      query
+    });
+
+    let response: DnsPacket = resolve(vec![server], &request(0x00, EDNS_DNSSEC_OK)).await;
+    handle.join().unwrap();
+    let opt: EdnsOpt = response.edns.unwrap();
+    assert_eq!(opt.extended_rcode, 0x01);
+    assert!(opt.dnssec_ok());
+    assert_eq!(response.answers.len(), 0x01);
+  }
+}
@@ -1,12 +1,32 @@
-use crate::{scalable_block, structs::DnsServerConfig, utils::initial_message};
+use crate::{
+  actions::handle_tcp_connection,
+  scalable_block,
+  structs::{
+    BlocklistSource,
+    ConfigReloader,
+    DnsCache,
+    DnsServerConfig,
+    HangupSignal,
+    LookupClient,
+    PACKET_BUFFER_SIZE,
+    SharedConfig,
+  },
+  utils::{bind_listener, initial_message},
+};
 use local_ip_address::local_ip;
 use std::{
   io::{Error, ErrorKind},
-  net::{IpAddr, Ipv4Addr, UdpSocket},
+  net::{IpAddr, SocketAddr, TcpListener, UdpSocket},
   sync::Arc,
-  time::Duration,
+  time::{Duration, SystemTime},
+};
+use tokio::{
+  net::{TcpListener as AsyncTcpListener, UdpSocket as AsyncUdpSocket},
+  select,
+  sync::{mpsc, watch},
+  task::{JoinSet, spawn_blocking},
+  time::sleep,
 };
-use tokio::sync::mpsc;
 
 // *
 
@@ -23,8 +43,9 @@ pub use tokio::spawn;
 #[derive(Debug)]
 pub struct DnsServer {
   pub config: DnsServerConfig,
-  lookup_client: UdpSocket,
-  socket: UdpSocket,
+  pub reloader: Option<ConfigReloader>,
+  tcp_listeners: Vec<TcpListener>,
+  sockets: Vec<UdpSocket>,
   debug: bool,
 
   #[cfg(feature = "scalability")]
@@ -34,22 +55,13 @@ pub struct DnsServer {
 impl DnsServer {
   #[cfg(feature = "scalability")]
   pub fn new(
-    nameservers: Vec<Ipv4Addr>,
+    listen: Vec<SocketAddr>,
+    nameservers: Vec<SocketAddr>,
     max_workers: usize,
     max_messages: usize,
     debug: bool,
   ) -> Result<Self, Error> {
-    let host_ip: IpAddr = local_ip().expect("Failed to detect local IP");
-    let default_timeout: Duration = Duration::from_secs(20);
-
-    let lookup_client: UdpSocket = UdpSocket::bind("0.0.0.0:0")?;
-    lookup_client.set_read_timeout(Some(default_timeout))?;
-    lookup_client.set_write_timeout(Some(default_timeout))?;
-
-    let server_socket: UdpSocket = UdpSocket::bind((host_ip, 53))?;
-    server_socket.set_read_timeout(Some(default_timeout))?;
-    server_socket.set_write_timeout(Some(default_timeout))?;
-    server_socket.set_nonblocking(true)?;
+    let (sockets, tcp_listeners) = Self::bind_all(listen)?;
 
     let (worker_tx, worker_rx) = mpsc::channel::<WorkerTask>(max_messages);
     tokio::spawn(async move {
@@ -57,99 +69,276 @@ impl DnsServer {
     });
 
     Ok(Self {
-      lookup_client,
-      socket: server_socket,
+      tcp_listeners,
+      sockets,
       config: DnsServerConfig::new(nameservers, max_workers, max_messages),
+      reloader: None,
       debug,
       worker_tx,
     })
   }
 
   #[cfg(not(feature = "scalability"))]
-  pub fn new(nameservers: Vec<Ipv4Addr>, debug: bool) -> Result<Self, Error> {
-    let host_ip: IpAddr = local_ip().expect("Failed to detect local IP");
-    let default_timeout: Duration = Duration::from_secs(20);
-
-    let lookup_client: UdpSocket = UdpSocket::bind("0.0.0.0:0")?;
-    lookup_client.set_read_timeout(Some(default_timeout))?;
-    lookup_client.set_write_timeout(Some(default_timeout))?;
-
-    let server_socket: UdpSocket = UdpSocket::bind((host_ip, 53))?;
-    server_socket.set_read_timeout(Some(default_timeout))?;
-    server_socket.set_write_timeout(Some(default_timeout))?;
-    server_socket.set_nonblocking(true)?;
+  pub fn new(
+    listen: Vec<SocketAddr>,
+    nameservers: Vec<SocketAddr>,
+    debug: bool,
+  ) -> Result<Self, Error> {
+    let (sockets, tcp_listeners) = Self::bind_all(listen)?;
 
     Ok(Self {
-      lookup_client,
-      socket: server_socket,
+      tcp_listeners,
+      sockets,
       config: DnsServerConfig::new(nameservers),
+      reloader: None,
       debug,
     })
   }
 
   // *
 
+  /// Binds one UDP socket and one TCP listener per address. Without addresses, the
+  /// server listens on the detected local IP at port 53.
+  fn bind_all(
+    mut listen: Vec<SocketAddr>,
+  ) -> Result<(Vec<UdpSocket>, Vec<TcpListener>), Error> {
+    if listen.is_empty() {
+      let host_ip: IpAddr = local_ip().map_err(|e| {
+        let message: String = format!("Failed to detect local IP: {}", e);
+        Error::new(ErrorKind::AddrNotAvailable, message)
+      })?;
+      listen.push(SocketAddr::new(host_ip, 53));
+    }
+
+    let mut sockets: Vec<UdpSocket> = Vec::with_capacity(listen.len());
+    let mut tcp_listeners: Vec<TcpListener> = Vec::with_capacity(listen.len());
+    for addr in listen {
+      let (socket, tcp_listener) = bind_listener(addr).map_err(|e| {
+        Error::new(e.kind(), format!("Failed to listen on {}: {}", addr, e))
+      })?;
+      sockets.push(socket);
+      tcp_listeners.push(tcp_listener);
+    }
+
+    Ok((sockets, tcp_listeners))
+  }
+
+  // *
+
+  /// Reads the blocklists of the current configuration again every reload interval,
+  /// keeping the lists loaded before when that fails. A new configuration restarts
+  /// the wait with its own blocklists.
+  fn reload_blocklists(shared_config: SharedConfig, debug: bool) {
+    tokio::spawn(async move {
+      let mut changes: watch::Receiver<Arc<DnsServerConfig>> = shared_config.subscribe();
+      loop {
+        let blocklist: Option<BlocklistSource> = shared_config
+          .load()
+          .blocklist
+          .clone()
+          .filter(|blocklist| !blocklist.reload_interval.is_zero());
+
+        let Some(blocklist) = blocklist else {
+          if changes.changed().await.is_err() {
+            return;
+          }
+          continue;
+        };
+        select! {
+          _ = sleep(blocklist.reload_interval) => {},
+          changed = changes.changed() => {
+            if changed.is_err() {
+              return;
+            }
+            continue;
+          },
+        }
+
+        let loaded: Result<usize, String> =
+          spawn_blocking(move || blocklist.load().map_err(|e| e.to_string()))
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|loaded| loaded);
+        match loaded {
+          Ok(rules) => {
+            if debug {
+              println!("[DEBUG]: Blocklists reloaded ({} rules)", rules);
+            }
+          },
+          Err(e) => println!("[ERROR]: Failed to reload blocklists: {}", e),
+        }
+      }
+    });
+  }
+
+  /// Builds the configuration again on SIGHUP, or when its file was modified, and
+  /// swaps it in. When that fails, the current configuration is kept and the error is
+  /// always reported.
+  fn reload_config(reloader: ConfigReloader, shared_config: SharedConfig, debug: bool) {
+    tokio::spawn(async move {
+      let mut hangup: HangupSignal = HangupSignal::new();
+      let mut modified: Option<SystemTime> = reloader.modified();
+      loop {
+        select! {
+          _ = hangup.recv() => {
+            if debug {
+              println!("[DEBUG]: SIGHUP received, reloading configuration");
+            }
+          },
+          _ = sleep(reloader.watch_interval), if !reloader.watch_interval.is_zero() => {
+            if reloader.modified() == modified {
+              continue;
+            }
+            if debug {
+              println!("[DEBUG]: '{}' modified, reloading", reloader.path.display());
+            }
+          },
+        }
+        // a file that failed to load is read again at the next check, even unchanged
+        let loading: Option<SystemTime> = reloader.modified();
+        let loader: ConfigReloader = reloader.clone();
+        let config: SharedConfig = shared_config.clone();
+        let reloaded: Result<(), Error> = spawn_blocking(move || loader.reload(&config))
+          .await
+          .map_err(Error::from)
+          .and_then(|reloaded| reloaded);
+        match reloaded {
+          Ok(()) => {
+            modified = loading;
+            if debug {
+              println!("[DEBUG]: Configuration reloaded");
+            }
+          },
+          Err(e) => println!("[ERROR]: Failed to reload configuration: {}", e),
+        }
+      }
+    });
+  }
+
+  // *
+
   pub async fn start(self) -> Result<(), Error> {
-    let socket: Arc<UdpSocket> = Arc::new(self.socket);
-    let lookup_client: Arc<UdpSocket> = Arc::new(self.lookup_client);
-    let config: Arc<DnsServerConfig> = Arc::new(self.config);
+    let shared_config: SharedConfig = SharedConfig::new(self.config);
+    let config: Arc<DnsServerConfig> = shared_config.load();
+    let lookup_client: Arc<LookupClient> =
+      Arc::new(LookupClient::new(config.query_timeout)?);
+    let cache: Arc<DnsCache> = Arc::new(DnsCache::new(config.cache_size));
+    let bind_addrs: Vec<SocketAddr> = self
+      .sockets
+      .iter()
+      .map(|socket| socket.local_addr())
+      .collect::<Result<_, Error>>()?;
     initial_message(
-      socket.local_addr()?,
+      &bind_addrs,
       config.max_workers_count,
       config.max_messages_count,
       self.debug,
     );
 
-    let mut buffer: [u8; 1280] = [0u8; 1280];
-    loop {
-      match socket.recv_from(&mut buffer) {
-        Ok((len, src)) => {
-          let data: Vec<u8> = buffer[..len].to_vec();
-          let socket: Arc<UdpSocket> = Arc::clone(&socket);
-          let lookup: Arc<UdpSocket> = Arc::clone(&lookup_client);
-          let config: Arc<DnsServerConfig> = Arc::clone(&config);
-
-          scalable_block!(
-            {
-              if let Err(e) = self
-                .worker_tx
-                .send(WorkerTask {
-                  data,
-                  src,
-                  socket: socket.clone(),
-                  lookup: lookup.clone(),
-                  config: config.clone(),
-                  debug: self.debug,
-                })
-                .await
-              {
-                if self.debug {
-                  println!("[DEBUG]: Error sending task to worker pool: {}", e);
+    let debug: bool = self.debug;
+    Self::reload_blocklists(shared_config.clone(), debug);
+    if let Some(reloader) = self.reloader {
+      Self::reload_config(reloader, shared_config.clone(), debug);
+    }
+
+    for tcp_listener in self.tcp_listeners {
+      let tcp_listener: AsyncTcpListener = AsyncTcpListener::from_std(tcp_listener)?;
+      let tcp_lookup: Arc<LookupClient> = Arc::clone(&lookup_client);
+      let tcp_config: SharedConfig = shared_config.clone();
+      let tcp_cache: Arc<DnsCache> = Arc::clone(&cache);
+      tokio::spawn(async move {
+        loop {
+          match tcp_listener.accept().await {
+            Ok((stream, src)) => {
+              let lookup: Arc<LookupClient> = Arc::clone(&tcp_lookup);
+              let config: SharedConfig = tcp_config.clone();
+              let cache: Arc<DnsCache> = Arc::clone(&tcp_cache);
+
+              tokio::spawn(async move {
+                if let Err(e) =
+                  handle_tcp_connection(config, cache, lookup, stream, src, debug).await
+                  && debug
+                {
+                  println!("[DEBUG]: Error handling TCP connection from {}: {}", src, e);
                 }
-              }
+              });
             },
-            {
-              spawn(async move {
-                if let Err(e) =
-                  handle_query(&config, &lookup, &socket, data, src, self.debug)
+            // errors such as running out of file descriptors last a while, the
+            // listener waits instead of retrying at once
+            Err(e) => {
+              println!("[ERROR]: TCP socket error: {}", e);
+              sleep(Duration::from_millis(0x64)).await;
+            },
+          }
+        }
+      });
+    }
+
+    // every listening socket has its own receive loop feeding the same pipeline
+    let mut receivers: JoinSet<()> = JoinSet::new();
+    for socket in self.sockets {
+      let socket: Arc<AsyncUdpSocket> = Arc::new(AsyncUdpSocket::from_std(socket)?);
+      let udp_lookup: Arc<LookupClient> = Arc::clone(&lookup_client);
+      let udp_config: SharedConfig = shared_config.clone();
+      let udp_cache: Arc<DnsCache> = Arc::clone(&cache);
+
+      #[cfg(feature = "scalability")]
+      let worker_tx: mpsc::Sender<WorkerTask> = self.worker_tx.clone();
+
+      receivers.spawn(async move {
+        let mut buffer: [u8; PACKET_BUFFER_SIZE] = [0u8; PACKET_BUFFER_SIZE];
+        loop {
+          match socket.recv_from(&mut buffer).await {
+            Ok((len, src)) => {
+              let data: Vec<u8> = buffer[..len].to_vec();
+              let socket: Arc<AsyncUdpSocket> = Arc::clone(&socket);
+              let lookup: Arc<LookupClient> = Arc::clone(&udp_lookup);
+              // queries keep the configuration current when they arrive
+              let config: Arc<DnsServerConfig> = udp_config.load();
+              let cache: Arc<DnsCache> = Arc::clone(&udp_cache);
+
+              scalable_block!(
                 {
-                  if self.debug {
-                    println!("[DEBUG]: Error handling query from {}: {}", src, e);
+                  if let Err(e) = worker_tx
+                    .send(WorkerTask {
+                      data,
+                      src,
+                      socket,
+                      lookup,
+                      config,
+                      cache,
+                      debug,
+                    })
+                    .await
+                    && debug
+                  {
+                    println!("[DEBUG]: Error sending task to worker pool: {}", e);
                   }
+                },
+                {
+                  spawn(async move {
+                    if let Err(e) =
+                      handle_query(&config, &cache, &lookup, &socket, data, src, debug)
+                        .await
+                      && debug
+                    {
+                      println!("[DEBUG]: Error handling query from {}: {}", src, e);
+                    }
+                  });
                 }
-              });
-            }
-          );
-        },
-        Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
-          continue; // non-blocking socket: continue pooling
-        },
-        Err(e) => {
-          if self.debug {
-            println!("[DEBUG]: Socket error: {}", e);
+              );
+            },
+            Err(e) => {
+              if debug {
+                println!("[DEBUG]: Socket error: {}", e);
+              }
+            },
           }
-        },
-      }
+        }
+      });
     }
+
+    while receivers.join_next().await.is_some() {}
+    Ok(())
   }
 }
@@ -0,0 +1,31 @@
+use std::net::IpAddr;
+
+// * >>> *
+
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub enum BlockMode {
+  /// The name is answered as if it did not exist
+  NxDomain,
+  /// The name is answered as existing, without records of any type
+  NoData,
+  /// The query is refused
+  #[default]
+  Refused,
+  /// A and AAAA queries are answered with the unspecified address (0.0.0.0 or ::)
+  NullIp,
+  /// A and AAAA queries are answered with these addresses (a block page server)
+  Sinkhole(Vec<IpAddr>),
+}
+
+impl BlockMode {
+  pub fn parse(value: &str, sinkhole: Vec<IpAddr>) -> Option<Self> {
+    match value.to_lowercase().as_str() {
+      "nxdomain" => Some(Self::NxDomain),
+      "nodata" => Some(Self::NoData),
+      "refused" => Some(Self::Refused),
+      "null" => Some(Self::NullIp),
+      "sinkhole" if !sinkhole.is_empty() => Some(Self::Sinkhole(sinkhole)),
+      _ => None,
+    }
+  }
+}
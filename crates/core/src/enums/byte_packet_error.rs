@@ -7,6 +7,7 @@ use std::{error::Error, io::Error as IoError};
 pub enum BytePacketError {
   MaxJumpsExceeded,
   InvalidPosition,
+  StringTooLong,
   LabelToLoong,
   EndOfBuffer,
 
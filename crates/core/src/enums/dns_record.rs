@@ -1,4 +1,4 @@
-use super::{BytePacketError as Error, QueryType};
+use super::{BytePacketError as Error, QueryClass, QueryType};
 use crate::{functions::*, legacy_code, structs::BytePacketBuffer as Buffer};
 use std::net::{Ipv4Addr, Ipv6Addr};
 
@@ -7,35 +7,74 @@ use std::net::{Ipv4Addr, Ipv6Addr};
 #[derive(PartialEq, Eq, Debug, Clone, Hash, PartialOrd, Ord)]
 pub enum DnsRecord {
   UNKNOWN {
-    data_len: u16,
+    data: Vec<u8>,
     domain: String,
     qtype: u16,
+    class: QueryClass,
     ttl: u32,
   },
   AAAA {
     address: Ipv6Addr,
     domain: String,
+    class: QueryClass,
+    ttl: u32,
+  },
+  SOA {
+    domain: String,
+    mname: String,
+    rname: String,
+    serial: u32,
+    refresh: u32,
+    retry: u32,
+    expire: u32,
+    minimum: u32,
+    class: QueryClass,
+    ttl: u32,
+  },
+  SRV {
+    priority: u16,
+    weight: u16,
+    port: u16,
+    domain: String,
+    host: String,
+    class: QueryClass,
+    ttl: u32,
+  },
+  TXT {
+    domain: String,
+    data: Vec<Vec<u8>>,
+    class: QueryClass,
     ttl: u32,
   },
   MX {
     priority: u16,
     domain: String,
     host: String,
+    class: QueryClass,
     ttl: u32,
   },
   CNAME {
     domain: String,
     host: String,
+    class: QueryClass,
     ttl: u32,
   },
   NS {
     domain: String,
     host: String,
+    class: QueryClass,
+    ttl: u32,
+  },
+  PTR {
+    domain: String,
+    host: String,
+    class: QueryClass,
     ttl: u32,
   },
   A {
     address: Ipv4Addr,
     domain: String,
+    class: QueryClass,
     ttl: u32,
   },
 }
@@ -47,27 +86,29 @@ impl DnsRecord {
 
     let qtype_num: u16 = buffer.read_u16()?;
     let qtype: QueryType = QueryType::from(qtype_num);
-    buffer.read_u16()?;
+    let class: QueryClass = QueryClass::from(buffer.read_u16()?);
 
     let ttl: u32 = buffer.read_u32()?;
     let data_len: u16 = buffer.read_u16()?;
+    let data_pos: usize = buffer.pos();
 
-    match qtype {
-      QueryType::UNKNOWN(_) => {
-        buffer.step(data_len as usize)?;
-        Ok(DnsRecord::UNKNOWN {
-          data_len,
-          domain,
-          qtype: qtype_num,
-          ttl,
-        })
+    let record: DnsRecord = match qtype {
+      QueryType::UNKNOWN(_) | QueryType::OPT => {
+        make_unknown_record(buffer, qtype_num, domain, class, ttl, data_len)
       },
-      QueryType::AAAA => make_aaaa_record(buffer, domain, ttl),
-      QueryType::A => make_a_record(buffer, domain, ttl),
-      QueryType::MX | QueryType::NS | QueryType::CNAME => {
-        make_mcn_record(qtype, buffer, domain, ttl)
+      QueryType::AAAA => make_aaaa_record(buffer, domain, class, ttl),
+      QueryType::A => make_a_record(buffer, domain, class, ttl),
+      QueryType::SOA => make_soa_record(buffer, domain, class, ttl),
+      QueryType::TXT => make_txt_record(buffer, domain, class, ttl, data_len),
+      QueryType::SRV => make_srv_record(buffer, domain, class, ttl),
+      QueryType::MX | QueryType::NS | QueryType::CNAME | QueryType::PTR => {
+        make_mcn_record(qtype, buffer, domain, class, ttl)
       },
-    }
+    }?;
+
+    // keep the buffer aligned with rdlength even if the rdata was read differently
+    buffer.seek(data_pos + data_len as usize)?;
+    Ok(record)
   }
 
   // *
@@ -78,42 +119,177 @@ impl DnsRecord {
       Self::AAAA {
         address,
         domain,
+        class,
         ttl,
-      } => write_aaaa_record(buffer, domain, address, ttl)?,
+      } => write_aaaa_record(buffer, domain, address, class, ttl)?,
       Self::MX {
         priority,
         domain,
         host,
+        class,
+        ttl,
+      } => write_mx_record(buffer, priority, domain, host, class, ttl)?,
+      Self::TXT {
+        domain,
+        data,
+        class,
+        ttl,
+      } => write_txt_record(buffer, domain, data, class, ttl)?,
+      Self::SRV {
+        priority,
+        weight,
+        port,
+        domain,
+        host,
+        class,
         ttl,
-      } => write_mx_record(buffer, priority, domain, host, ttl)?,
+      } => write_srv_record(buffer, priority, weight, port, domain, host, class, ttl)?,
+      Self::SOA {
+        domain,
+        mname,
+        rname,
+        serial,
+        refresh,
+        retry,
+        expire,
+        minimum,
+        class,
+        ttl,
+      } => write_soa_record(
+        buffer, domain, mname, rname, serial, refresh, retry, expire, minimum, class, ttl,
+      )?,
       Self::A {
         address,
         domain,
+        class,
+        ttl,
+      } => write_a_record(buffer, domain, address, class, ttl)?,
+      Self::CNAME {
+        domain,
+        host,
+        class,
+        ttl,
+      }
+      | Self::NS {
+        domain,
+        host,
+        class,
+        ttl,
+      }
+      | Self::PTR {
+        domain,
+        host,
+        class,
         ttl,
-      } => write_a_record(buffer, domain, address, ttl)?,
-      Self::CNAME { domain, host, ttl } | Self::NS { domain, host, ttl } => {
+      } => {
         let qtype: QueryType = legacy_code!(
           {
             match self {
               DnsRecord::CNAME { .. } => QueryType::CNAME,
               DnsRecord::NS { .. } => QueryType::CNAME,
+              DnsRecord::PTR { .. } => QueryType::PTR,
               _ => unreachable!(),
             }
           },
           {
-            if let DnsRecord::CNAME { .. } = self {
-              QueryType::CNAME
-            } else {
-              QueryType::NS
+            match self {
+              DnsRecord::CNAME { .. } => QueryType::CNAME,
+              DnsRecord::PTR { .. } => QueryType::PTR,
+              _ => QueryType::NS,
             }
           }
         );
 
-        write_cnns_record(qtype, buffer, domain, host, ttl)?;
+        write_cnns_record(qtype, buffer, domain, host, class, ttl)?;
       },
-      Self::UNKNOWN { .. } => legacy_code!({}, { return Err(Error::UnknownRecordError) }),
+      Self::UNKNOWN {
+        data,
+        domain,
+        qtype,
+        class,
+        ttl,
+      } => write_unknown_record(buffer, qtype, domain, data, class, ttl)?,
     }
 
     Ok(buffer.pos() - start_pos)
   }
+
+  // *
+
+  pub fn domain(&self) -> &str {
+    match self {
+      Self::UNKNOWN { domain, .. }
+      | Self::AAAA { domain, .. }
+      | Self::SOA { domain, .. }
+      | Self::TXT { domain, .. }
+      | Self::SRV { domain, .. }
+      | Self::MX { domain, .. }
+      | Self::CNAME { domain, .. }
+      | Self::NS { domain, .. }
+      | Self::PTR { domain, .. }
+      | Self::A { domain, .. } => domain,
+    }
+  }
+
+  pub fn qtype(&self) -> QueryType {
+    match self {
+      Self::UNKNOWN { qtype, .. } => QueryType::from(*qtype),
+      Self::AAAA { .. } => QueryType::AAAA,
+      Self::SOA { .. } => QueryType::SOA,
+      Self::TXT { .. } => QueryType::TXT,
+      Self::SRV { .. } => QueryType::SRV,
+      Self::MX { .. } => QueryType::MX,
+      Self::CNAME { .. } => QueryType::CNAME,
+      Self::NS { .. } => QueryType::NS,
+      Self::PTR { .. } => QueryType::PTR,
+      Self::A { .. } => QueryType::A,
+    }
+  }
+
+  // *
+
+  pub fn ttl(&self) -> u32 {
+    match self {
+      Self::UNKNOWN { ttl, .. }
+      | Self::AAAA { ttl, .. }
+      | Self::SOA { ttl, .. }
+      | Self::TXT { ttl, .. }
+      | Self::SRV { ttl, .. }
+      | Self::MX { ttl, .. }
+      | Self::CNAME { ttl, .. }
+      | Self::NS { ttl, .. }
+      | Self::PTR { ttl, .. }
+      | Self::A { ttl, .. } => *ttl,
+    }
+  }
+
+  pub fn set_ttl(&mut self, value: u32) {
+    match self {
+      Self::UNKNOWN { ttl, .. }
+      | Self::AAAA { ttl, .. }
+      | Self::SOA { ttl, .. }
+      | Self::TXT { ttl, .. }
+      | Self::SRV { ttl, .. }
+      | Self::MX { ttl, .. }
+      | Self::CNAME { ttl, .. }
+      | Self::NS { ttl, .. }
+      | Self::PTR { ttl, .. }
+      | Self::A { ttl, .. } => *ttl = value,
+    }
+  }
+
+  pub fn set_domain(&mut self, value: &str) {
+    match self {
+      Self::UNKNOWN { domain, .. }
+      | Self::AAAA { domain, .. }
+      | Self::SOA { domain, .. }
+      | Self::TXT { domain, .. }
+      | Self::SRV { domain, .. }
+      | Self::MX { domain, .. }
+      | Self::CNAME { domain, .. }
+      | Self::NS { domain, .. }
+      | Self::PTR { domain, .. }
+      | Self::A { domain, .. } => *domain = value.to_string(),
+    }
+  }
 }
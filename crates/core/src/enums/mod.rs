@@ -1,11 +1,17 @@
+mod block_mode;
 mod byte_packet_error;
 mod dns_record;
+mod query_class;
 mod query_type;
+mod resolve_mode;
 mod result_code;
 
 // * >>> *
 
+pub use block_mode::BlockMode;
 pub use byte_packet_error::BytePacketError;
 pub use dns_record::DnsRecord;
+pub use query_class::QueryClass;
 pub use query_type::QueryType;
+pub use resolve_mode::ResolveMode;
 pub use result_code::ResultCode;
@@ -0,0 +1,33 @@
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash, PartialOrd, Ord)]
+pub enum QueryClass {
+  UNKNOWN(u16),
+  NONE,
+  ANY,
+  CH,
+  HS,
+  IN,
+}
+
+impl QueryClass {
+  pub fn from(num: u16) -> Self {
+    match num {
+      0xFF => Self::ANY,
+      0xFE => Self::NONE,
+      0x04 => Self::HS,
+      0x03 => Self::CH,
+      0x01 => Self::IN,
+      _ => Self::UNKNOWN(num),
+    }
+  }
+
+  pub fn to_u16(self) -> u16 {
+    match self {
+      Self::ANY => 0xFF,
+      Self::NONE => 0xFE,
+      Self::HS => 0x04,
+      Self::CH => 0x03,
+      Self::IN => 0x01,
+      Self::UNKNOWN(num) => num,
+    }
+  }
+}
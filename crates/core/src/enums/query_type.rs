@@ -3,6 +3,11 @@ pub enum QueryType {
   UNKNOWN(u16),
   CNAME,
   AAAA,
+  PTR,
+  SRV,
+  TXT,
+  SOA,
+  OPT,
   MX,
   NS,
   A,
@@ -11,8 +16,13 @@ pub enum QueryType {
 impl QueryType {
   pub fn from(num: u16) -> Self {
     match num {
+      0x29 => Self::OPT,
+      0x21 => Self::SRV,
       0x1C => Self::AAAA,
+      0x10 => Self::TXT,
       0x0F => Self::MX,
+      0x0C => Self::PTR,
+      0x06 => Self::SOA,
       0x05 => Self::CNAME,
       0x02 => Self::NS,
       0x01 => Self::A,
@@ -22,8 +32,13 @@ impl QueryType {
 
   pub fn to_u16(self) -> u16 {
     match self {
+      Self::OPT => 0x29,
+      Self::SRV => 0x21,
       Self::AAAA => 0x1C,
+      Self::TXT => 0x10,
       Self::MX => 0x0F,
+      Self::PTR => 0x0C,
+      Self::SOA => 0x06,
       Self::CNAME => 0x05,
       Self::NS => 0x02,
       Self::A => 0x01,
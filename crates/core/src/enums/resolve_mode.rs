@@ -0,0 +1,18 @@
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum ResolveMode {
+  /// Queries are sent to the configured nameservers, which resolve them
+  #[default]
+  Forward,
+  /// Queries are resolved iteratively, starting from the root servers
+  Recursive,
+}
+
+impl ResolveMode {
+  pub fn parse(value: &str) -> Option<Self> {
+    match value.to_lowercase().as_str() {
+      "forward" => Some(Self::Forward),
+      "recursive" => Some(Self::Recursive),
+      _ => None,
+    }
+  }
+}
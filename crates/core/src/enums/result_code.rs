@@ -16,7 +16,7 @@ impl ResultCode {
       0x03 => Self::NxDomain,
       0x04 => Self::NoTimp,
       0x05 => Self::Refused,
-      0x00 | _ => ResultCode::NoError,
+      _ => ResultCode::NoError,
     }
   }
 }
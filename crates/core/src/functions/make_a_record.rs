@@ -1,5 +1,5 @@
 use crate::{
-  enums::{BytePacketError as Error, DnsRecord},
+  enums::{BytePacketError as Error, DnsRecord, QueryClass},
   legacy_code,
   structs::BytePacketBuffer as Buffer,
 };
@@ -10,6 +10,7 @@ use std::net::Ipv4Addr;
 pub fn make_a_record(
   buffer: &mut Buffer,
   domain: String,
+  class: QueryClass,
   ttl: u32,
 ) -> Result<DnsRecord, Error> {
   let raw_ip: u32 = buffer.read_u32()?;
@@ -28,6 +29,7 @@ pub fn make_a_record(
   Ok(DnsRecord::A {
     address: addr,
     domain,
+    class,
     ttl,
   })
 }
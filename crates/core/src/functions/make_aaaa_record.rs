@@ -1,5 +1,5 @@
 use crate::{
-  enums::{BytePacketError as Error, DnsRecord},
+  enums::{BytePacketError as Error, DnsRecord, QueryClass},
   structs::BytePacketBuffer as Buffer,
 };
 use std::net::Ipv6Addr;
@@ -9,6 +9,7 @@ use std::net::Ipv6Addr;
 pub fn make_aaaa_record(
   buffer: &mut Buffer,
   domain: String,
+  class: QueryClass,
   ttl: u32,
 ) -> Result<DnsRecord, Error> {
   const BYTE_MASK: u32 = 0xFFFF;
@@ -32,6 +33,7 @@ pub fn make_aaaa_record(
   Ok(DnsRecord::AAAA {
     address: addr,
     domain,
+    class,
     ttl,
   })
 }
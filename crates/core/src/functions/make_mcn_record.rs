@@ -1,5 +1,5 @@
 use crate::{
-  enums::{BytePacketError as Error, DnsRecord, QueryType},
+  enums::{BytePacketError as Error, DnsRecord, QueryClass, QueryType},
   legacy_code,
   structs::BytePacketBuffer as Buffer,
 };
@@ -10,18 +10,21 @@ pub fn make_mcn_record(
   qtype: QueryType,
   buffer: &mut Buffer,
   domain: String,
+  class: QueryClass,
   ttl: u32,
 ) -> Result<DnsRecord, Error> {
   let mut host: String = String::new();
   buffer.read_qname(&mut host)?;
 
   match qtype {
-    QueryType::CNAME => Ok(DnsRecord::CNAME { domain, host, ttl }),
-    QueryType::NS => Ok(DnsRecord::NS { domain, host, ttl }),
+    QueryType::CNAME => Ok(DnsRecord::CNAME { domain, host, class, ttl }),
+    QueryType::NS => Ok(DnsRecord::NS { domain, host, class, ttl }),
+    QueryType::PTR => Ok(DnsRecord::PTR { domain, host, class, ttl }),
     QueryType::MX => Ok(DnsRecord::MX {
       priority: buffer.read_u16()?,
       domain,
       host,
+      class,
       ttl,
     }),
     _ => legacy_code!({ unreachable!() }, {
@@ -0,0 +1,32 @@
+use crate::{
+  enums::{BytePacketError as Error, DnsRecord, QueryClass},
+  structs::BytePacketBuffer as Buffer,
+};
+
+// * >>> *
+
+pub fn make_soa_record(
+  buffer: &mut Buffer,
+  domain: String,
+  class: QueryClass,
+  ttl: u32,
+) -> Result<DnsRecord, Error> {
+  let mut mname: String = String::new();
+  buffer.read_qname(&mut mname)?;
+
+  let mut rname: String = String::new();
+  buffer.read_qname(&mut rname)?;
+
+  Ok(DnsRecord::SOA {
+    domain,
+    mname,
+    rname,
+    serial: buffer.read_u32()?,
+    refresh: buffer.read_u32()?,
+    retry: buffer.read_u32()?,
+    expire: buffer.read_u32()?,
+    minimum: buffer.read_u32()?,
+    class,
+    ttl,
+  })
+}
@@ -0,0 +1,30 @@
+use crate::{
+  enums::{BytePacketError as Error, DnsRecord, QueryClass},
+  structs::BytePacketBuffer as Buffer,
+};
+
+// * >>> *
+
+pub fn make_srv_record(
+  buffer: &mut Buffer,
+  domain: String,
+  class: QueryClass,
+  ttl: u32,
+) -> Result<DnsRecord, Error> {
+  let priority: u16 = buffer.read_u16()?;
+  let weight: u16 = buffer.read_u16()?;
+  let port: u16 = buffer.read_u16()?;
+
+  let mut host: String = String::new();
+  buffer.read_qname(&mut host)?;
+
+  Ok(DnsRecord::SRV {
+    priority,
+    weight,
+    port,
+    domain,
+    host,
+    class,
+    ttl,
+  })
+}
@@ -0,0 +1,29 @@
+use crate::{
+  enums::{BytePacketError as Error, DnsRecord, QueryClass},
+  structs::BytePacketBuffer as Buffer,
+};
+
+// * >>> *
+
+pub fn make_txt_record(
+  buffer: &mut Buffer,
+  domain: String,
+  class: QueryClass,
+  ttl: u32,
+  data_len: u16,
+) -> Result<DnsRecord, Error> {
+  let end: usize = buffer.pos() + data_len as usize;
+  let mut data: Vec<Vec<u8>> = Vec::new();
+
+  while buffer.pos() < end {
+    let length: usize = buffer.read()? as usize;
+    data.push(buffer.get_range(buffer.pos(), length)?.to_vec());
+    buffer.step(length)?;
+  }
+
+  if buffer.pos() != end {
+    return Err(Error::InvalidPosition);
+  }
+
+  Ok(DnsRecord::TXT { domain, data, class, ttl })
+}
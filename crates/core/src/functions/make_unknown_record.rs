@@ -0,0 +1,26 @@
+use crate::{
+  enums::{BytePacketError as Error, DnsRecord, QueryClass},
+  structs::BytePacketBuffer as Buffer,
+};
+
+// * >>> *
+
+pub fn make_unknown_record(
+  buffer: &mut Buffer,
+  qtype: u16,
+  domain: String,
+  class: QueryClass,
+  ttl: u32,
+  data_len: u16,
+) -> Result<DnsRecord, Error> {
+  let data: Vec<u8> = buffer.get_range(buffer.pos(), data_len as usize)?.to_vec();
+  buffer.step(data_len as usize)?;
+
+  Ok(DnsRecord::UNKNOWN {
+    data,
+    domain,
+    qtype,
+    class,
+    ttl,
+  })
+}
@@ -1,19 +1,35 @@
 mod make_a_record;
 mod make_aaaa_record;
 mod make_mcn_record;
+mod make_soa_record;
+mod make_srv_record;
+mod make_txt_record;
+mod make_unknown_record;
 
 mod write_a_record;
 mod write_aaaa_record;
 mod write_cnns_record;
 mod write_mx_record;
+mod write_soa_record;
+mod write_srv_record;
+mod write_txt_record;
+mod write_unknown_record;
 
 // * >>> *
 
 pub use make_a_record::make_a_record;
 pub use make_aaaa_record::make_aaaa_record;
 pub use make_mcn_record::make_mcn_record;
+pub use make_soa_record::make_soa_record;
+pub use make_srv_record::make_srv_record;
+pub use make_txt_record::make_txt_record;
+pub use make_unknown_record::make_unknown_record;
 
 pub use write_a_record::write_a_record;
 pub use write_aaaa_record::write_aaaa_record;
 pub use write_cnns_record::write_cnns_record;
 pub use write_mx_record::write_mx_record;
+pub use write_soa_record::write_soa_record;
+pub use write_srv_record::write_srv_record;
+pub use write_txt_record::write_txt_record;
+pub use write_unknown_record::write_unknown_record;
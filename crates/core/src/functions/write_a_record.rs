@@ -1,5 +1,5 @@
 use crate::{
-  enums::{BytePacketError as Error, QueryType},
+  enums::{BytePacketError as Error, QueryClass, QueryType},
   structs::BytePacketBuffer as Buffer,
 };
 use std::net::Ipv4Addr;
@@ -8,14 +8,15 @@ use std::net::Ipv4Addr;
 
 pub fn write_a_record(
   buffer: &mut Buffer,
-  domain: &String,
+  domain: &str,
   addr: &Ipv4Addr,
+  class: &QueryClass,
   ttl: &u32,
 ) -> Result<(), Error> {
   buffer.write_qname(domain)?;
 
   buffer.write_u16(QueryType::A.to_u16())?;
-  buffer.write_u16(0x01)?;
+  buffer.write_u16(class.to_u16())?;
   buffer.write_u32(*ttl)?;
   buffer.write_u16(0x04)?;
 
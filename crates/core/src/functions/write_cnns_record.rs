@@ -1,5 +1,5 @@
 use crate::{
-  enums::{BytePacketError as Error, QueryType},
+  enums::{BytePacketError as Error, QueryClass, QueryType},
   structs::BytePacketBuffer as Buffer,
 };
 
@@ -8,14 +8,15 @@ use crate::{
 pub fn write_cnns_record(
   qtype: QueryType,
   buffer: &mut Buffer,
-  domain: &String,
-  host: &String,
+  domain: &str,
+  host: &str,
+  class: &QueryClass,
   ttl: &u32,
 ) -> Result<(), Error> {
   buffer.write_qname(domain)?;
 
   buffer.write_u16(qtype.to_u16())?;
-  buffer.write_u16(0x01)?;
+  buffer.write_u16(class.to_u16())?;
   buffer.write_u32(*ttl)?;
 
   let pos: usize = buffer.pos();
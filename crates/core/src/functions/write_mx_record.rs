@@ -1,5 +1,5 @@
 use crate::{
-  enums::{BytePacketError as Error, QueryType},
+  enums::{BytePacketError as Error, QueryClass, QueryType},
   structs::BytePacketBuffer as Buffer,
 };
 
@@ -8,20 +8,21 @@ use crate::{
 pub fn write_mx_record(
   buffer: &mut Buffer,
   priority: &u16,
-  domain: &String,
-  host: &String,
+  domain: &str,
+  host: &str,
+  class: &QueryClass,
   ttl: &u32,
 ) -> Result<(), Error> {
-  buffer.write_qname(&domain)?;
+  buffer.write_qname(domain)?;
 
   buffer.write_u16(QueryType::MX.to_u16())?;
-  buffer.write_u16(0x01)?;
+  buffer.write_u16(class.to_u16())?;
   buffer.write_u32(*ttl)?;
 
   let pos: usize = buffer.pos();
   buffer.write_u16(0x00)?;
   buffer.write_u16(*priority)?;
-  buffer.write_qname(&host)?;
+  buffer.write_qname(host)?;
 
   let size: usize = buffer.pos() - (pos + 0x02);
   buffer.set_u16(pos, size as u16)?;
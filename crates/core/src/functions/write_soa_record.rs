@@ -0,0 +1,41 @@
+use crate::{
+  enums::{BytePacketError as Error, QueryClass, QueryType},
+  structs::BytePacketBuffer as Buffer,
+};
+
+// * >>> *
+
+#[allow(clippy::too_many_arguments)]
+pub fn write_soa_record(
+  buffer: &mut Buffer,
+  domain: &str,
+  mname: &str,
+  rname: &str,
+  serial: &u32,
+  refresh: &u32,
+  retry: &u32,
+  expire: &u32,
+  minimum: &u32,
+  class: &QueryClass,
+  ttl: &u32,
+) -> Result<(), Error> {
+  buffer.write_qname(domain)?;
+
+  buffer.write_u16(QueryType::SOA.to_u16())?;
+  buffer.write_u16(class.to_u16())?;
+  buffer.write_u32(*ttl)?;
+
+  let pos: usize = buffer.pos();
+  buffer.write_u16(0x00)?;
+  buffer.write_qname(mname)?;
+  buffer.write_qname(rname)?;
+  buffer.write_u32(*serial)?;
+  buffer.write_u32(*refresh)?;
+  buffer.write_u32(*retry)?;
+  buffer.write_u32(*expire)?;
+  buffer.write_u32(*minimum)?;
+
+  let size: usize = buffer.pos() - (pos + 0x02);
+  buffer.set_u16(pos, size as u16)?;
+  Ok(())
+}
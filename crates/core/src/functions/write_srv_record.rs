@@ -0,0 +1,35 @@
+use crate::{
+  enums::{BytePacketError as Error, QueryClass, QueryType},
+  structs::BytePacketBuffer as Buffer,
+};
+
+// * >>> *
+
+#[allow(clippy::too_many_arguments)]
+pub fn write_srv_record(
+  buffer: &mut Buffer,
+  priority: &u16,
+  weight: &u16,
+  port: &u16,
+  domain: &str,
+  host: &str,
+  class: &QueryClass,
+  ttl: &u32,
+) -> Result<(), Error> {
+  buffer.write_qname(domain)?;
+
+  buffer.write_u16(QueryType::SRV.to_u16())?;
+  buffer.write_u16(class.to_u16())?;
+  buffer.write_u32(*ttl)?;
+
+  let pos: usize = buffer.pos();
+  buffer.write_u16(0x00)?;
+  buffer.write_u16(*priority)?;
+  buffer.write_u16(*weight)?;
+  buffer.write_u16(*port)?;
+  buffer.write_qname(host)?;
+
+  let size: usize = buffer.pos() - (pos + 0x02);
+  buffer.set_u16(pos, size as u16)?;
+  Ok(())
+}
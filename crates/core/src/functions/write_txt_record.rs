@@ -0,0 +1,36 @@
+use crate::{
+  enums::{BytePacketError as Error, QueryClass, QueryType},
+  structs::BytePacketBuffer as Buffer,
+};
+
+// * >>> *
+
+pub fn write_txt_record(
+  buffer: &mut Buffer,
+  domain: &str,
+  data: &[Vec<u8>],
+  class: &QueryClass,
+  ttl: &u32,
+) -> Result<(), Error> {
+  const MAX_STRING_LENGTH: usize = 0xFF;
+  buffer.write_qname(domain)?;
+
+  buffer.write_u16(QueryType::TXT.to_u16())?;
+  buffer.write_u16(class.to_u16())?;
+  buffer.write_u32(*ttl)?;
+
+  let pos: usize = buffer.pos();
+  buffer.write_u16(0x00)?;
+  for string in data {
+    if string.len() > MAX_STRING_LENGTH {
+      return Err(Error::StringTooLong);
+    }
+
+    buffer.write(string.len() as u8)?;
+    buffer.write_bytes(string)?;
+  }
+
+  let size: usize = buffer.pos() - (pos + 0x02);
+  buffer.set_u16(pos, size as u16)?;
+  Ok(())
+}
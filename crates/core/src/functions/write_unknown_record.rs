@@ -0,0 +1,25 @@
+use crate::{
+  enums::{BytePacketError as Error, QueryClass},
+  structs::BytePacketBuffer as Buffer,
+};
+
+// * >>> *
+
+pub fn write_unknown_record(
+  buffer: &mut Buffer,
+  qtype: &u16,
+  domain: &str,
+  data: &[u8],
+  class: &QueryClass,
+  ttl: &u32,
+) -> Result<(), Error> {
+  buffer.write_qname(domain)?;
+
+  buffer.write_u16(*qtype)?;
+  buffer.write_u16(class.to_u16())?;
+  buffer.write_u32(*ttl)?;
+
+  // rdata of unknown types is relayed untouched (RFC 3597)
+  buffer.write_u16(data.len() as u16)?;
+  buffer.write_bytes(data)
+}
@@ -0,0 +1,104 @@
+use super::NameTrie;
+
+// * >>> *
+
+/// Blocked names and their exceptions, each rule covering either a name itself or the
+/// names below it
+#[derive(Debug, Clone)]
+pub struct Blocklist {
+  pub blocked_names: NameTrie<()>,
+  pub blocked_subdomains: NameTrie<()>,
+  pub allowed_names: NameTrie<()>,
+  pub allowed_subdomains: NameTrie<()>,
+}
+
+impl Blocklist {
+  pub fn new() -> Self {
+    Self {
+      blocked_names: NameTrie::new(),
+      blocked_subdomains: NameTrie::new(),
+      allowed_names: NameTrie::new(),
+      allowed_subdomains: NameTrie::new(),
+    }
+  }
+
+  // *
+
+  pub fn block(&mut self, name: &str) {
+    self.blocked_names.insert(name, ());
+  }
+
+  pub fn block_subdomains(&mut self, name: &str) {
+    self.blocked_subdomains.insert(name, ());
+  }
+
+  pub fn allow(&mut self, name: &str) {
+    self.allowed_names.insert(name, ());
+  }
+
+  pub fn allow_subdomains(&mut self, name: &str) {
+    self.allowed_subdomains.insert(name, ());
+  }
+
+  /// Exceptions always win over blocking rules
+  pub fn is_blocked(&self, name: &str) -> bool {
+    let matches = |names: &NameTrie<()>, subdomains: &NameTrie<()>| {
+      names.get(name).is_some() || subdomains.find_ancestor(name).is_some()
+    };
+
+    !matches(&self.allowed_names, &self.allowed_subdomains)
+      && matches(&self.blocked_names, &self.blocked_subdomains)
+  }
+}
+
+impl Default for Blocklist {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+// * >>> *
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn blocks_names_and_subdomains() {
+    let mut blocklist: Blocklist = Blocklist::new();
+    blocklist.block("ads.example.com");
+    blocklist.block_subdomains("tracker.example.com");
+
+    assert!(blocklist.is_blocked("ads.example.com"));
+    assert!(blocklist.is_blocked("ADS.example.com."));
+    assert!(!blocklist.is_blocked("www.ads.example.com"));
+    assert!(blocklist.is_blocked("a.tracker.example.com"));
+    assert!(!blocklist.is_blocked("tracker.example.com"));
+    assert!(!blocklist.is_blocked("example.com"));
+  }
+
+  #[test]
+  fn exceptions_win_over_blocking_rules() {
+    let mut blocklist: Blocklist = Blocklist::new();
+    blocklist.block("example.com");
+    blocklist.block_subdomains("example.com");
+    blocklist.allow("www.example.com");
+    blocklist.allow_subdomains("cdn.example.com");
+
+    assert!(blocklist.is_blocked("example.com"));
+    assert!(blocklist.is_blocked("ads.example.com"));
+    assert!(!blocklist.is_blocked("www.example.com"));
+    // an exception for a name does not cover the names below it
+    assert!(blocklist.is_blocked("a.www.example.com"));
+    assert!(!blocklist.is_blocked("img.cdn.example.com"));
+    assert!(blocklist.is_blocked("cdn.example.com"));
+  }
+
+  #[test]
+  fn exceptions_apply_whatever_the_rule_order() {
+    let mut blocklist: Blocklist = Blocklist::new();
+    blocklist.allow("ads.example.com");
+    blocklist.block("ads.example.com");
+    assert!(!blocklist.is_blocked("ads.example.com"));
+  }
+}
@@ -0,0 +1,81 @@
+use super::Blocklist;
+use crate::{
+  enums::{BlockMode, BytePacketError as Error},
+  utils::parse_blocklist,
+};
+use std::{
+  fs::read_to_string,
+  path::PathBuf,
+  sync::{Arc, RwLock},
+  time::Duration,
+};
+
+// * >>> *
+
+/// Blocklist files and allowlist exceptions, re-read from disk every `reload_interval`
+/// (never when zero). Blocked names are answered as `block_mode` asks, or as the
+/// server default when not set. Clones share the loaded lists.
+#[derive(Debug, Clone)]
+pub struct BlocklistSource {
+  pub files: Vec<PathBuf>,
+  pub allow_files: Vec<PathBuf>,
+  pub allow: Vec<String>,
+  pub reload_interval: Duration,
+  pub block_mode: Option<BlockMode>,
+  current: Arc<RwLock<Arc<Blocklist>>>,
+}
+
+impl BlocklistSource {
+  pub fn new(
+    files: Vec<PathBuf>,
+    allow_files: Vec<PathBuf>,
+    allow: Vec<String>,
+    reload_interval: Duration,
+    block_mode: Option<BlockMode>,
+  ) -> Self {
+    Self {
+      files,
+      allow_files,
+      allow,
+      reload_interval,
+      block_mode,
+      current: Arc::new(RwLock::new(Arc::new(Blocklist::new()))),
+    }
+  }
+
+  // *
+
+  /// Reads every list and swaps the result in, returning the number of rules. On
+  /// errors, the lists loaded before are kept.
+  pub fn load(&self) -> Result<usize, Error> {
+    let mut blocklist: Blocklist = Blocklist::new();
+    let mut rules: usize = parse_blocklist(&self.allow.join("\n"), &mut blocklist, true);
+
+    let files = self
+      .files
+      .iter()
+      .map(|path| (path, false))
+      .chain(self.allow_files.iter().map(|path| (path, true)));
+    for (path, allow) in files {
+      let text: String = read_to_string(path).map_err(|e| {
+        Error::Custom(format!("Failed to read blocklist '{}': {}", path.display(), e))
+      })?;
+      rules += parse_blocklist(&text, &mut blocklist, allow);
+    }
+
+    let Ok(mut current) = self.current.write() else {
+      return Err(Error::Custom("Blocklist lock poisoned".to_string()));
+    };
+    *current = Arc::new(blocklist);
+    Ok(rules)
+  }
+
+  pub fn is_blocked(&self, name: &str) -> bool {
+    // the lock is only held to take the current lists, not while searching them
+    let blocklist: Arc<Blocklist> = match self.current.read() {
+      Ok(current) => Arc::clone(&current),
+      Err(_) => return false,
+    };
+    blocklist.is_blocked(name)
+  }
+}
@@ -1,19 +1,35 @@
+#![allow(clippy::question_mark)]
+
 use crate::{enums::BytePacketError as Error, legacy_block, legacy_code};
 use std::result::Result;
 
 // * >>> *
 
 pub const PACKET_BUFFER_SIZE: usize = 0x500;
+pub const UDP_PAYLOAD_SIZE: usize = 0x200;
+pub const TCP_MESSAGE_SIZE: usize = 0xFFFF;
 pub struct BytePacketBuffer {
   pub position: usize,
-  pub buffer: [u8; PACKET_BUFFER_SIZE],
+  pub buffer: Vec<u8>,
+  limit: usize,
 }
 
 impl BytePacketBuffer {
   pub fn new() -> Self {
     Self {
       position: 0x00,
-      buffer: [0x00; PACKET_BUFFER_SIZE],
+      buffer: vec![0x00; PACKET_BUFFER_SIZE],
+      limit: PACKET_BUFFER_SIZE,
+    }
+  }
+
+  /// Buffer that grows while it is written, up to `limit` bytes (TCP messages can be
+  /// up to 65535 bytes long, RFC 1035 section 4.2.2)
+  pub fn growable(limit: usize) -> Self {
+    Self {
+      position: 0x00,
+      buffer: Vec::with_capacity(PACKET_BUFFER_SIZE.min(limit)),
+      limit,
     }
   }
 
@@ -39,7 +55,7 @@ impl BytePacketBuffer {
     legacy_block!(
       // * === LEGACY === *
       {
-        if self.position >= PACKET_BUFFER_SIZE {
+        if self.position >= self.buffer.len() {
           return Err(Error::EndOfBuffer);
         }
 
@@ -112,7 +128,7 @@ impl BytePacketBuffer {
   pub fn get(&mut self, pos: usize) -> Result<u8, Error> {
     legacy_code!(
       {
-        if pos >= PACKET_BUFFER_SIZE {
+        if pos >= self.buffer.len() {
           return Err(Error::EndOfBuffer);
         }
         Ok(unsafe { *self.buffer.get_unchecked(pos) })
@@ -121,7 +137,7 @@ impl BytePacketBuffer {
         self
           .buffer
           .get(pos)
-          .map(|&byte| byte)
+          .copied()
           .ok_or_else(|| Error::EndOfBuffer)
       }
     )
@@ -130,7 +146,7 @@ impl BytePacketBuffer {
   pub fn get_range(&mut self, start: usize, len: usize) -> Result<&[u8], Error> {
     legacy_block!(
       {
-        if start + len >= PACKET_BUFFER_SIZE {
+        if start + len > self.buffer.len() {
           return Err(Error::EndOfBuffer);
         }
 
@@ -148,9 +164,13 @@ impl BytePacketBuffer {
   // *
 
   pub fn write(&mut self, val: u8) -> Result<(), Error> {
+    if self.position == self.buffer.len() && self.buffer.len() < self.limit {
+      self.buffer.push(0x00);
+    }
+
     legacy_block!(
       {
-        if self.position >= PACKET_BUFFER_SIZE {
+        if self.position >= self.buffer.len() {
           return Err(Error::EndOfBuffer);
         }
 
@@ -281,7 +301,8 @@ impl BytePacketBuffer {
   pub fn write_qname(&mut self, qname: &str) -> Result<(), Error> {
     const MAX_LABEL_LENGTH: usize = 0x3F;
 
-    for label in qname.split('.') {
+    // empty labels (root name, trailing dot) are covered by the final zero byte
+    for label in qname.split('.').filter(|label| !label.is_empty()) {
       let length: usize = label.len();
 
       if length > MAX_LABEL_LENGTH {
@@ -316,6 +337,10 @@ impl BytePacketBuffer {
   pub fn set(&mut self, pos: usize, val: u8) -> Result<(), Error> {
     legacy_block!(
       {
+        if pos >= self.buffer.len() {
+          return Err(Error::EndOfBuffer);
+        }
+
         unsafe {
           *self.buffer.get_unchecked_mut(pos) = val;
         }
@@ -337,3 +362,7 @@ impl BytePacketBuffer {
     self.set(pos + 0x01, (val & 0xFF) as u8)
   }
 }
+
+impl Default for BytePacketBuffer {
+  fn default() -> Self { Self::new() }
+}
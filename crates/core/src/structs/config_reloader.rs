@@ -0,0 +1,109 @@
+use super::{DnsServerConfig, SharedConfig};
+use std::{
+  fmt::{Debug, Formatter, Result as FmtResult},
+  fs::metadata,
+  io::Error,
+  path::{Path, PathBuf},
+  sync::Arc,
+  time::{Duration, SystemTime},
+};
+
+// * >>> *
+
+type ConfigLoader = dyn Fn(&Path) -> Result<DnsServerConfig, Error> + Send + Sync;
+
+/// Builds the configuration again from its file, on SIGHUP and, every `watch_interval`
+/// (never when zero), when the file was modified
+#[derive(Clone)]
+pub struct ConfigReloader {
+  pub path: PathBuf,
+  pub watch_interval: Duration,
+  loader: Arc<ConfigLoader>,
+}
+
+impl ConfigReloader {
+  pub fn new<F>(path: PathBuf, watch_interval: Duration, loader: F) -> Self
+  where
+    F: Fn(&Path) -> Result<DnsServerConfig, Error> + Send + Sync + 'static,
+  {
+    Self {
+      path,
+      watch_interval,
+      loader: Arc::new(loader),
+    }
+  }
+
+  // *
+
+  pub fn load(&self) -> Result<DnsServerConfig, Error> {
+    (self.loader)(&self.path)
+  }
+
+  /// Builds the configuration again and swaps it in, keeping the current one when
+  /// that fails
+  pub fn reload(&self, shared_config: &SharedConfig) -> Result<(), Error> {
+    shared_config.store(self.load()?);
+    Ok(())
+  }
+
+  pub fn modified(&self) -> Option<SystemTime> {
+    metadata(&self.path).and_then(|m| m.modified()).ok()
+  }
+}
+
+impl Debug for ConfigReloader {
+  fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+    f.debug_struct("ConfigReloader")
+      .field("path", &self.path)
+      .field("watch_interval", &self.watch_interval)
+      .finish_non_exhaustive()
+  }
+}
+
+// * >>> *
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::scalable_block;
+  use std::{
+    io::ErrorKind,
+    net::{Ipv4Addr, SocketAddr},
+  };
+
+  fn config(last_octet: u8) -> DnsServerConfig {
+    let nameservers: Vec<SocketAddr> =
+      vec![SocketAddr::from((Ipv4Addr::new(192, 0, 2, last_octet), 53))];
+    scalable_block!({ DnsServerConfig::new(nameservers, 0x01, 0x01) }, {
+      DnsServerConfig::new(nameservers)
+    })
+  }
+
+  fn reloader(result: fn() -> Result<DnsServerConfig, Error>) -> ConfigReloader {
+    ConfigReloader::new(PathBuf::from("server.toml"), Duration::ZERO, move |_| result())
+  }
+
+  // *
+
+  #[test]
+  fn failed_reload_keeps_the_current_configuration() {
+    let shared_config: SharedConfig = SharedConfig::new(config(0x01));
+    let before: Arc<DnsServerConfig> = shared_config.load();
+
+    let reloader: ConfigReloader =
+      reloader(|| Err(Error::new(ErrorKind::InvalidData, "invalid TOML")));
+    assert!(reloader.reload(&shared_config).is_err());
+    assert!(Arc::ptr_eq(&before, &shared_config.load()));
+  }
+
+  #[test]
+  fn reload_swaps_the_configuration_in() {
+    let shared_config: SharedConfig = SharedConfig::new(config(0x01));
+    let before: Arc<DnsServerConfig> = shared_config.load();
+
+    reloader(|| Ok(config(0x02))).reload(&shared_config).unwrap();
+    assert_eq!(shared_config.load().nameservers, config(0x02).nameservers);
+    // queries in flight keep the configuration they took
+    assert_eq!(before.nameservers, config(0x01).nameservers);
+  }
+}
@@ -0,0 +1,120 @@
+use super::MAX_CACHE_TTL;
+use crate::utils::in_zone;
+use std::{
+  collections::{BTreeMap, HashMap},
+  net::SocketAddr,
+  sync::{Mutex, MutexGuard},
+  time::{Duration, Instant},
+};
+
+// * >>> *
+
+const MAX_DELEGATIONS: usize = 0x1000;
+
+#[derive(Debug, Clone)]
+struct Delegation {
+  servers: Vec<SocketAddr>,
+  expires_at: Instant,
+  last_used: u64,
+}
+
+#[derive(Debug, Default)]
+struct DelegationState {
+  zones: HashMap<String, Delegation>,
+  usage: BTreeMap<u64, String>,
+  tick: u64,
+}
+
+/// Nameservers of the zone cuts met while resolving iteratively, so later lookups
+/// start at the closest known zone instead of the root servers
+#[derive(Debug, Default)]
+pub struct DelegationCache {
+  state: Mutex<DelegationState>,
+}
+
+impl DelegationCache {
+  fn key(zone: &str) -> String {
+    zone.trim_end_matches('.').to_lowercase()
+  }
+
+  // *
+
+  /// Closest cached zone cut above `qname` that is strictly below `zone`
+  pub fn closest(&self, qname: &str, zone: &str) -> Option<(String, Vec<SocketAddr>)> {
+    let now: Instant = Instant::now();
+    let mut state: MutexGuard<DelegationState> = self.state.lock().ok()?;
+    let state: &mut DelegationState = &mut state;
+
+    let zone: String = Self::key(zone);
+    let mut name: &str = &Self::key(qname);
+    while name.len() > zone.len() && in_zone(name, &zone) {
+      if let Some(delegation) = state.zones.get_mut(name) {
+        if delegation.expires_at > now {
+          state.tick += 0x01;
+          state.usage.remove(&delegation.last_used);
+          state.usage.insert(state.tick, name.to_string());
+          delegation.last_used = state.tick;
+          return Some((name.to_string(), delegation.servers.clone()));
+        }
+
+        let last_used: u64 = delegation.last_used;
+        state.zones.remove(name);
+        state.usage.remove(&last_used);
+      }
+
+      let Some((_, parent)) = name.split_once('.') else {
+        break;
+      };
+      name = parent;
+    }
+    None
+  }
+
+  // *
+
+  /// Stores the servers of a zone cut for the TTL of its records, at most a day.
+  /// Delegations with a zero TTL are not stored.
+  pub fn insert(&self, zone: &str, servers: &[SocketAddr], ttl: u32) {
+    if ttl == 0x00 || servers.is_empty() {
+      return;
+    }
+
+    let key: String = Self::key(zone);
+    let now: Instant = Instant::now();
+    let mut state: MutexGuard<DelegationState> = match self.state.lock() {
+      Ok(state) => state,
+      Err(_) => return,
+    };
+    let state: &mut DelegationState = &mut state;
+
+    if let Some(delegation) = state.zones.remove(&key) {
+      state.usage.remove(&delegation.last_used);
+    }
+
+    while state.zones.len() >= MAX_DELEGATIONS {
+      let Some((_, oldest)) = state.usage.pop_first() else {
+        break;
+      };
+      state.zones.remove(&oldest);
+    }
+
+    state.tick += 0x01;
+    state.usage.insert(state.tick, key.clone());
+    state.zones.insert(key, Delegation {
+      servers: servers.to_vec(),
+      expires_at: now + Duration::from_secs(ttl.min(MAX_CACHE_TTL) as u64),
+      last_used: state.tick,
+    });
+  }
+
+  /// Forgets a zone cut whose servers no longer answer
+  pub fn remove(&self, zone: &str) {
+    let Ok(mut state) = self.state.lock() else {
+      return;
+    };
+
+    if let Some(delegation) = state.zones.remove(&Self::key(zone)) {
+      state.usage.remove(&delegation.last_used);
+    }
+  }
+}
@@ -0,0 +1,309 @@
+use super::{DnsPacket, DnsQuestion};
+use crate::enums::{DnsRecord, QueryClass, QueryType, ResultCode};
+use std::{
+  collections::{BTreeMap, HashMap},
+  sync::{Mutex, MutexGuard},
+  time::{Duration, Instant},
+};
+
+// * >>> *
+
+pub const MAX_CACHE_TTL: u32 = 0x15180;
+/// Answers asked with the DO bit carry DNSSEC records, they are kept apart
+type CacheKey = (String, QueryType, QueryClass, bool);
+
+#[derive(Debug, Clone)]
+struct CacheEntry {
+  rescode: ResultCode,
+  answers: Vec<DnsRecord>,
+  authorities: Vec<DnsRecord>,
+  resources: Vec<DnsRecord>,
+  inserted_at: Instant,
+  expires_at: Instant,
+  last_used: u64,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+  entries: HashMap<CacheKey, CacheEntry>,
+  usage: BTreeMap<u64, CacheKey>,
+  tick: u64,
+}
+
+#[derive(Debug)]
+pub struct DnsCache {
+  state: Mutex<CacheState>,
+  max_entries: usize,
+}
+
+impl DnsCache {
+  pub fn new(max_entries: usize) -> Self {
+    Self {
+      state: Mutex::new(CacheState::default()),
+      max_entries,
+    }
+  }
+
+  // *
+
+  fn key(question: &DnsQuestion, dnssec_ok: bool) -> CacheKey {
+    (question.name.to_lowercase(), question.qtype, question.qclass, dnssec_ok)
+  }
+
+  // *
+
+  pub fn get(&self, question: &DnsQuestion, dnssec_ok: bool) -> Option<DnsPacket> {
+    if self.max_entries == 0 {
+      return None;
+    }
+
+    let key: CacheKey = Self::key(question, dnssec_ok);
+    let now: Instant = Instant::now();
+    let mut state: MutexGuard<CacheState> = self.state.lock().ok()?;
+    let state: &mut CacheState = &mut state;
+
+    let entry: &mut CacheEntry = state.entries.get_mut(&key)?;
+    if entry.expires_at <= now {
+      let last_used: u64 = entry.last_used;
+      state.entries.remove(&key);
+      state.usage.remove(&last_used);
+      return None;
+    }
+
+    state.tick += 0x01;
+    state.usage.remove(&entry.last_used);
+    state.usage.insert(state.tick, key);
+    entry.last_used = state.tick;
+
+    // records leave the cache with the time they have already spent in it removed
+    let elapsed: u32 = now.duration_since(entry.inserted_at).as_secs() as u32;
+    let age = |records: &Vec<DnsRecord>| -> Vec<DnsRecord> {
+      records
+        .iter()
+        .cloned()
+        .map(|mut record| {
+          record.set_ttl(record.ttl().saturating_sub(elapsed));
+          record
+        })
+        .collect()
+    };
+
+    let mut packet: DnsPacket = DnsPacket::new();
+    packet.header.rescode = entry.rescode;
+    packet.answers = age(&entry.answers);
+    packet.authorities = age(&entry.authorities);
+    packet.resources = age(&entry.resources);
+    Some(packet)
+  }
+
+  // *
+
+  pub fn insert(&self, question: &DnsQuestion, dnssec_ok: bool, packet: &DnsPacket) {
+    if self.max_entries == 0 {
+      return;
+    }
+
+    let Some(ttl) = self.cache_ttl(packet) else {
+      return;
+    };
+
+    let key: CacheKey = Self::key(question, dnssec_ok);
+    let now: Instant = Instant::now();
+    let mut state: MutexGuard<CacheState> = match self.state.lock() {
+      Ok(state) => state,
+      Err(_) => return,
+    };
+    let state: &mut CacheState = &mut state;
+
+    if let Some(entry) = state.entries.remove(&key) {
+      state.usage.remove(&entry.last_used);
+    }
+
+    while state.entries.len() >= self.max_entries {
+      let Some((_, oldest)) = state.usage.pop_first() else {
+        break;
+      };
+      state.entries.remove(&oldest);
+    }
+
+    state.tick += 0x01;
+    state.usage.insert(state.tick, key.clone());
+    state.entries.insert(key, CacheEntry {
+      rescode: packet.header.rescode,
+      answers: packet.answers.clone(),
+      authorities: packet.authorities.clone(),
+      resources: packet.resources.clone(),
+      inserted_at: now,
+      expires_at: now + Duration::from_secs(ttl as u64),
+      last_used: state.tick,
+    });
+  }
+
+  // *
+
+  fn cache_ttl(&self, packet: &DnsPacket) -> Option<u32> {
+    // extended RCODEs are errors, such as BADVERS or BADCOOKIE, which are not cached
+    if packet.edns.as_ref().is_some_and(|edns| edns.extended_rcode != 0x00) {
+      return None;
+    }
+
+    let negative: bool = match packet.header.rescode {
+      ResultCode::NxDomain => true,
+      ResultCode::NoError => packet.answers.is_empty(),
+      _ => return None,
+    };
+
+    // NXDOMAIN and NODATA answers are cached for the SOA minimum (RFC 2308), an
+    // empty answer without a SOA is a referral, or a negative answer that is not
+    // cacheable (RFC 2308 section 5)
+    let ttl: u32 = if negative {
+      packet.authorities.iter().find_map(|record| match record {
+        DnsRecord::SOA { minimum, ttl, .. } => Some(*minimum.min(ttl)),
+        _ => None,
+      })?
+    } else {
+      packet
+        .answers
+        .iter()
+        .chain(packet.authorities.iter())
+        .map(DnsRecord::ttl)
+        .min()?
+    };
+
+    (ttl > 0).then_some(ttl.min(MAX_CACHE_TTL))
+  }
+}
+
+// * >>> *
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::structs::EdnsOpt;
+  use std::{net::Ipv4Addr, thread::sleep};
+
+  fn question(name: &str) -> DnsQuestion {
+    DnsQuestion::new(QueryType::A, QueryClass::IN, name.to_string())
+  }
+
+  fn answer(name: &str, ttl: u32) -> DnsPacket {
+    let mut packet: DnsPacket = DnsPacket::new();
+    packet.answers.push(DnsRecord::A {
+      domain: name.to_string(),
+      class: QueryClass::IN,
+      address: Ipv4Addr::new(0x0A, 0x00, 0x00, 0x01),
+      ttl,
+    });
+    packet
+  }
+
+  fn negative(rescode: ResultCode, minimum: u32, ttl: u32) -> DnsPacket {
+    let mut packet: DnsPacket = DnsPacket::new();
+    packet.header.rescode = rescode;
+    packet.authorities.push(DnsRecord::SOA {
+      domain: "example.com".to_string(),
+      class: QueryClass::IN,
+      mname: "ns.example.com".to_string(),
+      rname: "hostmaster.example.com".to_string(),
+      serial: 0x01,
+      refresh: 0x0E10,
+      retry: 0x0384,
+      expire: 0x093A80,
+      minimum,
+      ttl,
+    });
+    packet
+  }
+
+  // *
+
+  #[test]
+  fn ttls_count_down_while_cached() {
+    let cache: DnsCache = DnsCache::new(0x10);
+    cache.insert(&question("example.com"), false, &answer("example.com", 300));
+    sleep(Duration::from_millis(0x44C));
+
+    let packet: DnsPacket = cache.get(&question("Example.com"), false).unwrap();
+    assert_eq!(packet.answers[0].ttl(), 299);
+  }
+
+  #[test]
+  fn expired_entries_are_removed() {
+    let cache: DnsCache = DnsCache::new(0x10);
+    cache.insert(&question("example.com"), false, &answer("example.com", 0x01));
+    sleep(Duration::from_millis(0x44C));
+
+    assert!(cache.get(&question("example.com"), false).is_none());
+    assert!(cache.state.lock().unwrap().entries.is_empty());
+  }
+
+  #[test]
+  fn least_recently_used_entry_is_evicted() {
+    let cache: DnsCache = DnsCache::new(0x02);
+    cache.insert(&question("a.example.com"), false, &answer("a.example.com", 300));
+    cache.insert(&question("b.example.com"), false, &answer("b.example.com", 300));
+    assert!(cache.get(&question("a.example.com"), false).is_some());
+    cache.insert(&question("c.example.com"), false, &answer("c.example.com", 300));
+
+    assert!(cache.get(&question("a.example.com"), false).is_some());
+    assert!(cache.get(&question("b.example.com"), false).is_none());
+    assert!(cache.get(&question("c.example.com"), false).is_some());
+  }
+
+  #[test]
+  fn ttls_are_capped() {
+    let cache: DnsCache = DnsCache::new(0x10);
+    assert_eq!(cache.cache_ttl(&answer("example.com", 300)), Some(300));
+    assert_eq!(
+      cache.cache_ttl(&answer("example.com", 0xFFFFFFFF)),
+      Some(MAX_CACHE_TTL)
+    );
+    assert_eq!(cache.cache_ttl(&answer("example.com", 0x00)), None);
+  }
+
+  #[test]
+  fn negative_answers_use_the_soa_minimum() {
+    let cache: DnsCache = DnsCache::new(0x10);
+    let packet: DnsPacket = negative(ResultCode::NxDomain, 0x12C, 0xE10);
+    assert_eq!(cache.cache_ttl(&packet), Some(0x12C));
+    // the SOA TTL bounds the minimum as well
+    let packet: DnsPacket = negative(ResultCode::NoError, 0xE10, 0x12C);
+    assert_eq!(cache.cache_ttl(&packet), Some(0x12C));
+  }
+
+  #[test]
+  fn empty_answers_without_a_soa_are_not_cached() {
+    let cache: DnsCache = DnsCache::new(0x10);
+    let mut packet: DnsPacket = DnsPacket::new();
+    packet.header.rescode = ResultCode::NxDomain;
+    assert_eq!(cache.cache_ttl(&packet), None);
+
+    // a referral has NS records where a NODATA answer has its SOA
+    packet.header.rescode = ResultCode::NoError;
+    packet.authorities.push(DnsRecord::NS {
+      domain: "example.com".to_string(),
+      class: QueryClass::IN,
+      host: "ns.example.com".to_string(),
+      ttl: 0xE10,
+    });
+    assert_eq!(cache.cache_ttl(&packet), None);
+  }
+
+  #[test]
+  fn dnssec_answers_are_kept_apart() {
+    let cache: DnsCache = DnsCache::new(0x10);
+    cache.insert(&question("example.com"), true, &answer("example.com", 300));
+    assert!(cache.get(&question("example.com"), false).is_none());
+    assert!(cache.get(&question("example.com"), true).is_some());
+  }
+
+  #[test]
+  fn extended_errors_are_not_cached() {
+    let cache: DnsCache = DnsCache::new(0x10);
+    let mut packet: DnsPacket = answer("example.com", 300);
+    let mut opt: EdnsOpt = EdnsOpt::new(0x0200);
+    opt.extended_rcode = 0x01;
+    packet.edns = Some(opt);
+    assert_eq!(cache.cache_ttl(&packet), None);
+  }
+}
@@ -54,7 +54,7 @@ impl DnsHeader {
 
     let flags: u16 = buffer.read_u16()?;
     let a: u8 = (flags >> 0x08) as u8;
-    let b: u8 = (flags & 0xFF as u16) as u8;
+    let b: u8 = (flags & 0xFF) as u8;
 
     self.recursion_desired = a & 0x01 != 0;
     self.truncated_message = a & 0x02 != 0;
@@ -62,7 +62,7 @@ impl DnsHeader {
     self.opcode = (a >> 0x03) & 0x0F;
     self.response = a & 0x80 != 0;
 
-    self.rescode = ResultCode::from(b & 0xFF);
+    self.rescode = ResultCode::from(b & 0x0F);
     self.checking_disabled = b & 0x10 != 0;
     self.authed_data = b & 0x20 != 0;
     self.z = b & 0x40 != 0;
@@ -102,3 +102,7 @@ impl DnsHeader {
     buffer.write_u16(self.resource_entries)
   }
 }
+
+impl Default for DnsHeader {
+  fn default() -> Self { Self::new() }
+}
@@ -1,7 +1,15 @@
-use super::{BytePacketBuffer as Buffer, DnsHeader, DnsQuestion};
-use crate::enums::{BytePacketError as Error, DnsRecord, QueryType};
+use super::{
+  BytePacketBuffer as Buffer,
+  DnsHeader,
+  DnsQuestion,
+  EdnsOpt,
+  PACKET_BUFFER_SIZE,
+  TCP_MESSAGE_SIZE,
+  UDP_PAYLOAD_SIZE,
+};
+use crate::enums::{BytePacketError as Error, DnsRecord, QueryClass, QueryType};
 
-use std::net::Ipv4Addr;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 
 // * >>> *
 
@@ -12,6 +20,7 @@ pub struct DnsPacket {
   pub questions: Vec<DnsQuestion>,
   pub resources: Vec<DnsRecord>,
   pub answers: Vec<DnsRecord>,
+  pub edns: Option<EdnsOpt>,
 }
 
 impl DnsPacket {
@@ -22,6 +31,7 @@ impl DnsPacket {
       questions: Vec::new(),
       resources: Vec::new(),
       answers: Vec::new(),
+      edns: None,
     }
   }
 
@@ -34,6 +44,7 @@ impl DnsPacket {
     packet.questions = (0..packet.header.questions)
       .map(|_| {
         let mut question: DnsQuestion = DnsQuestion {
+          qclass: QueryClass::IN,
           qtype: QueryType::UNKNOWN(0),
           name: String::new(),
         };
@@ -51,20 +62,42 @@ impl DnsPacket {
       .map(|_| DnsRecord::read(buffer))
       .collect::<Result<_, _>>()?;
 
-    packet.resources = (0..packet.header.resource_entries)
-      .map(|_| DnsRecord::read(buffer))
-      .collect::<Result<_, _>>()?;
+    for _ in 0..packet.header.resource_entries {
+      let start_pos: usize = buffer.pos();
+      buffer.read_qname(&mut String::new())?;
+      let qtype: QueryType = QueryType::from(buffer.read_u16()?);
+      buffer.seek(start_pos)?;
+
+      if qtype == QueryType::OPT {
+        packet.edns = Some(EdnsOpt::read(buffer)?);
+      } else {
+        packet.resources.push(DnsRecord::read(buffer)?);
+      }
+    }
 
     Ok(packet)
   }
 
+  pub fn from_bytes(data: &[u8]) -> Result<Self, Error> {
+    let mut buffer: Buffer = Buffer::growable(TCP_MESSAGE_SIZE);
+    buffer.write_bytes(data)?;
+    buffer.seek(0x00)?;
+
+    Self::from_buffer(&mut buffer)
+  }
+
   // *
 
-  pub fn write(&mut self, buffer: &mut Buffer) -> Result<(), Error> {
+  fn count_entries(&mut self) {
     self.header.questions = self.questions.len() as u16;
     self.header.answers = self.answers.len() as u16;
     self.header.authoritative_entries = self.authorities.len() as u16;
-    self.header.resource_entries = self.resources.len() as u16;
+    self.header.resource_entries =
+      (self.resources.len() + self.edns.is_some() as usize) as u16;
+  }
+
+  pub fn write(&mut self, buffer: &mut Buffer) -> Result<(), Error> {
+    self.count_entries();
     self.header.write(buffer)?;
 
     for question in self.questions.iter_mut() {
@@ -79,10 +112,80 @@ impl DnsPacket {
     for record in &self.resources {
       record.write(buffer)?;
     }
+    if let Some(edns) = &self.edns {
+      edns.write(buffer)?;
+    }
 
     Ok(())
   }
 
+  pub fn write_truncated(
+    &mut self,
+    buffer: &mut Buffer,
+    max_size: usize,
+  ) -> Result<(), Error> {
+    // the OPT record is always kept, the room it takes is set aside first
+    let mut opt_buffer: Buffer = Buffer::growable(max_size);
+    if let Some(edns) = &self.edns {
+      edns.write(&mut opt_buffer)?;
+    }
+    let limit: usize = max_size.saturating_sub(opt_buffer.pos());
+
+    buffer.seek(0x00)?;
+    self.header.write(buffer)?;
+    for question in self.questions.iter_mut() {
+      question.write(buffer)?;
+    }
+    if buffer.pos() > limit {
+      return Err(Error::EndOfBuffer);
+    }
+
+    // records are written in order until one does not fit, the ones after it are lost
+    let mut kept: [usize; 0x03] = [0x00; 0x03];
+    'sections: for (section, records) in
+      [&self.answers, &self.authorities, &self.resources].into_iter().enumerate()
+    {
+      for record in records {
+        let start: usize = buffer.pos();
+        match record.write(buffer) {
+          Ok(_) if buffer.pos() <= limit => kept[section] += 0x01,
+          Ok(_) | Err(Error::EndOfBuffer) => {
+            buffer.seek(start)?;
+            break 'sections;
+          },
+          Err(e) => return Err(e),
+        }
+      }
+    }
+
+    // additional records are optional, losing them does not require the TC bit
+    if kept[0x00] < self.answers.len() || kept[0x01] < self.authorities.len() {
+      self.header.truncated_message = true;
+    }
+    self.answers.truncate(kept[0x00]);
+    self.authorities.truncate(kept[0x01]);
+    self.resources.truncate(kept[0x02]);
+
+    if let Some(edns) = &self.edns {
+      edns.write(buffer)?;
+    }
+
+    // the header is written again with the counts and TC bit of what was kept
+    let end: usize = buffer.pos();
+    self.count_entries();
+    buffer.seek(0x00)?;
+    self.header.write(buffer)?;
+    buffer.seek(end)
+  }
+
+  // *
+
+  pub fn udp_payload_size(&self) -> usize {
+    self.edns.as_ref().map_or(UDP_PAYLOAD_SIZE, |edns| {
+      (edns.payload_size as usize).clamp(UDP_PAYLOAD_SIZE, PACKET_BUFFER_SIZE)
+    })
+  }
+
   // *
 
   pub fn get_random_a(&self) -> Option<Ipv4Addr> {
@@ -95,6 +198,16 @@ impl DnsPacket {
     })
   }
 
+  pub fn get_random_aaaa(&self) -> Option<Ipv6Addr> {
+    self.answers.iter().find_map(|record| {
+      if let DnsRecord::AAAA { address, .. } = record {
+        Some(*address)
+      } else {
+        None
+      }
+    })
+  }
+
   // *
 
   pub fn get_ns<'a>(
@@ -116,22 +229,21 @@ impl DnsPacket {
 
   // *
 
-  pub fn get_resolved_ns(&self, qname: &str) -> Option<Ipv4Addr> {
+  pub fn get_resolved_ns(&self, qname: &str) -> Option<IpAddr> {
+    // A glue is preferred, AAAA glue is used for IPv6-only nameservers
     self.get_ns(qname).find_map(|(_, host)| {
-      self
-        .resources
-        .iter()
-        .filter_map(|record| {
-          if let DnsRecord::A {
+      let glue = |ipv6: bool| -> Option<IpAddr> {
+        self.resources.iter().find_map(|record| match record {
+          DnsRecord::A {
+            address, domain, ..
+          } if !ipv6 && domain == host => Some(IpAddr::V4(*address)),
+          DnsRecord::AAAA {
             address, domain, ..
-          } = record
-          {
-            (domain == host).then_some(*address)
-          } else {
-            None
-          }
+          } if ipv6 && domain == host => Some(IpAddr::V6(*address)),
+          _ => None,
         })
-        .next()
+      };
+      glue(false).or_else(|| glue(true))
     })
   }
 
@@ -141,3 +253,94 @@ impl DnsPacket {
     self.get_ns(qname).map(|(_, host)| host).next()
   }
 }
+
+impl Default for DnsPacket {
+  fn default() -> Self { Self::new() }
+}
+
+// * >>> *
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn records(domain: &str, count: u8) -> Vec<DnsRecord> {
+    (0..count)
+      .map(|i| DnsRecord::A {
+        domain: domain.to_string(),
+        class: QueryClass::IN,
+        address: Ipv4Addr::new(0x0A, 0x00, 0x00, i),
+        ttl: 300,
+      })
+      .collect()
+  }
+
+  fn packet(answers: u8, resources: u8) -> DnsPacket {
+    let mut packet: DnsPacket = DnsPacket::new();
+    packet.questions.push(DnsQuestion::new(
+      QueryType::A,
+      QueryClass::IN,
+      "example.com".to_string(),
+    ));
+    packet.answers = records("example.com", answers);
+    packet.resources = records("ns.example.com", resources);
+    packet
+  }
+
+  // *
+
+  #[test]
+  fn write_truncated_cuts_answers_and_sets_tc() {
+    let mut packet: DnsPacket = packet(0x40, 0x00);
+    let mut buffer: Buffer = Buffer::new();
+    packet.write_truncated(&mut buffer, 0x200).unwrap();
+    assert!(buffer.pos() <= 0x200);
+    assert!(packet.header.truncated_message);
+    assert!(!packet.answers.is_empty() && packet.answers.len() < 0x40);
+
+    buffer.seek(0x00).unwrap();
+    let parsed: DnsPacket = DnsPacket::from_buffer(&mut buffer).unwrap();
+    assert!(parsed.header.truncated_message);
+    assert_eq!(parsed.answers.len(), packet.answers.len());
+  }
+
+  #[test]
+  fn write_truncated_keeps_the_opt_record_within_the_edns_limit() {
+    let mut packet: DnsPacket = packet(0x40, 0x00);
+    packet.edns = Some(EdnsOpt::new(0x0400));
+    let max_size: usize = packet.udp_payload_size();
+    let mut buffer: Buffer = Buffer::new();
+    packet.write_truncated(&mut buffer, max_size).unwrap();
+    assert!(buffer.pos() <= 0x0400);
+    assert!(packet.header.truncated_message);
+
+    // as many answers as fit are kept, one more goes over the limit
+    let answer_size: usize = packet.answers[0].write(&mut Buffer::new()).unwrap();
+    assert!(buffer.pos() + answer_size > 0x0400);
+
+    buffer.seek(0x00).unwrap();
+    let parsed: DnsPacket = DnsPacket::from_buffer(&mut buffer).unwrap();
+    assert_eq!(parsed.edns, packet.edns);
+    assert_eq!(parsed.answers.len(), packet.answers.len());
+  }
+
+  #[test]
+  fn write_truncated_drops_additional_records_without_tc() {
+    let mut packet: DnsPacket = packet(0x02, 0x20);
+    let mut buffer: Buffer = Buffer::new();
+    packet.write_truncated(&mut buffer, 0x200).unwrap();
+    assert!(buffer.pos() <= 0x200);
+    assert!(!packet.header.truncated_message);
+    assert_eq!(packet.answers.len(), 0x02);
+    assert!(packet.resources.len() < 0x20);
+  }
+
+  #[test]
+  fn write_truncated_keeps_a_packet_that_fits() {
+    let mut packet: DnsPacket = packet(0x02, 0x02);
+    let mut buffer: Buffer = Buffer::new();
+    packet.write_truncated(&mut buffer, 0x200).unwrap();
+    assert!(!packet.header.truncated_message);
+    assert_eq!((packet.answers.len(), packet.resources.len()), (0x02, 0x02));
+  }
+}
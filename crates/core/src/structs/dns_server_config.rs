@@ -1,12 +1,50 @@
-use super::LookAtRecord;
-use std::net::{Ipv4Addr, Ipv6Addr};
+use super::{
+  BlocklistSource,
+  ForwardZone,
+  LookAtRecord,
+  LookAtService,
+  LookAtSoa,
+  NameTrie,
+  Zone,
+  ZoneStore,
+};
+use crate::{
+  enums::{BlockMode, ResolveMode},
+  utils::{ROOT_HINTS, in_zone, parse_root_hints, reverse_name},
+};
+use std::{
+  collections::HashMap,
+  net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+  time::Duration,
+};
 
 // * >>> *
 
+pub const DEFAULT_TCP_IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+pub const DEFAULT_QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+pub const DEFAULT_CACHE_SIZE: usize = 0x1000;
+pub const DEFAULT_NEGATIVE_TTL: u32 = 0x12C;
+pub const DEFAULT_BLOCK_TTL: u32 = 0x0A;
 #[derive(Debug, Clone)]
 pub struct DnsServerConfig {
-  pub nameservers: Vec<Ipv4Addr>,
+  pub nameservers: Vec<SocketAddr>,
+  pub root_hints: Vec<SocketAddr>,
+  pub mode: ResolveMode,
+  pub forward_zones: Vec<ForwardZone>,
+  pub zones: ZoneStore,
   pub look_at: Vec<LookAtRecord>,
+  pub look_at_index: NameTrie<usize>,
+  pub services: HashMap<String, Vec<LookAtService>>,
+  pub reverse_names: HashMap<String, Vec<String>>,
+  pub chaos_names: HashMap<String, String>,
+  pub aliases: HashMap<String, String>,
+  pub blocklist: Option<BlocklistSource>,
+  pub block_mode: BlockMode,
+  pub block_ttl: u32,
+  pub tcp_idle_timeout: Duration,
+  pub query_timeout: Duration,
+  pub cache_size: usize,
+  pub negative_ttl: u32,
 
   #[cfg(feature = "scalability")]
   pub max_messages_count: usize,
@@ -17,49 +55,188 @@ pub struct DnsServerConfig {
 impl DnsServerConfig {
   #[cfg(feature = "scalability")]
   pub fn new(
-    nameservers: Vec<Ipv4Addr>,
+    nameservers: Vec<SocketAddr>,
     max_workers: usize,
     max_messages: usize,
   ) -> Self {
     Self {
       nameservers,
+      root_hints: parse_root_hints(ROOT_HINTS).unwrap_or_default(),
+      mode: ResolveMode::Forward,
+      forward_zones: Vec::new(),
+      zones: ZoneStore::new(),
       look_at: Vec::new(),
+      look_at_index: NameTrie::new(),
+      services: HashMap::new(),
+      reverse_names: HashMap::new(),
+      chaos_names: HashMap::new(),
+      aliases: HashMap::new(),
+      blocklist: None,
+      block_mode: BlockMode::Refused,
+      block_ttl: DEFAULT_BLOCK_TTL,
+      tcp_idle_timeout: DEFAULT_TCP_IDLE_TIMEOUT,
+      query_timeout: DEFAULT_QUERY_TIMEOUT,
+      cache_size: DEFAULT_CACHE_SIZE,
+      negative_ttl: DEFAULT_NEGATIVE_TTL,
       max_messages_count: if max_messages > 0 { max_messages } else { 1 },
       max_workers_count: if max_workers > 0 { max_workers } else { 1 },
     }
   }
 
   #[cfg(not(feature = "scalability"))]
-  pub fn new(nameservers: Vec<Ipv4Addr>) -> Self {
+  pub fn new(nameservers: Vec<SocketAddr>) -> Self {
     Self {
       nameservers,
+      root_hints: parse_root_hints(ROOT_HINTS).unwrap_or_default(),
+      mode: ResolveMode::Forward,
+      forward_zones: Vec::new(),
+      zones: ZoneStore::new(),
       look_at: Vec::new(),
+      look_at_index: NameTrie::new(),
+      services: HashMap::new(),
+      reverse_names: HashMap::new(),
+      chaos_names: HashMap::new(),
+      aliases: HashMap::new(),
+      blocklist: None,
+      block_mode: BlockMode::Refused,
+      block_ttl: DEFAULT_BLOCK_TTL,
+      tcp_idle_timeout: DEFAULT_TCP_IDLE_TIMEOUT,
+      query_timeout: DEFAULT_QUERY_TIMEOUT,
+      cache_size: DEFAULT_CACHE_SIZE,
+      negative_ttl: DEFAULT_NEGATIVE_TTL,
     }
   }
 
   // *
 
-  fn __add_look_at_record(
+  /// Adds an entry answering its names (exact or "*." wildcards) from its records
+  pub fn look_at(&mut self, record: LookAtRecord) {
+    if record.domains.is_empty() {
+      return;
+    }
+
+    if record.reverse {
+      let addrs = record
+        .ipv4_addrs
+        .iter()
+        .filter(|ip| !ip.is_unspecified())
+        .map(|ip| IpAddr::V4(*ip))
+        .chain(
+          record
+            .ipv6_addrs
+            .iter()
+            .filter(|ip| !ip.is_unspecified())
+            .map(|ip| IpAddr::V6(*ip)),
+        );
+
+      for addr in addrs {
+        let hosts: &mut Vec<String> =
+          self.reverse_names.entry(reverse_name(addr)).or_default();
+        // wildcards stand for many names, none of them to publish
+        for domain in record.domains.iter().filter(|d| !d.starts_with("*.")) {
+          if !hosts.contains(domain) {
+            hosts.push(domain.clone());
+          }
+        }
+      }
+    }
+
+    for domain in &record.domains {
+      self.look_at_index.insert(domain, self.look_at.len());
+    }
+    self.look_at.push(record);
+  }
+
+  /// Adds an entry from its records, like `look_at` with a `LookAtRecord`
+  #[deprecated(note = "pass a LookAtRecord to look_at")]
+  #[allow(clippy::too_many_arguments)]
+  pub fn look_many(
     &mut self,
     domains: Vec<String>,
     ipv4: Vec<Ipv4Addr>,
     ipv6: Vec<Ipv6Addr>,
+    txt: Vec<String>,
+    raw: Vec<(u16, Vec<u8>)>,
+    ns: Vec<String>,
+    mx: Vec<(u16, String)>,
+    soa: Option<LookAtSoa>,
+    block_mode: Option<BlockMode>,
+    reverse: bool,
   ) {
-    if !domains.is_empty() {
-      self.look_at.push(LookAtRecord::new(domains, ipv4, ipv6));
-    }
+    self.look_at(LookAtRecord {
+      txt_records: txt,
+      raw_records: raw,
+      ns_hosts: ns,
+      mx_hosts: mx,
+      soa,
+      block_mode,
+      reverse,
+      ..LookAtRecord::new(domains, ipv4, ipv6)
+    });
   }
 
-  pub fn look_at(&mut self, domain: String, ipv4: Vec<Ipv4Addr>, ipv6: Vec<Ipv6Addr>) {
-    self.__add_look_at_record(vec![domain], ipv4, ipv6);
+  /// Finds the entry answering a name: its exact entry, or the wildcard at its
+  /// closest encloser
+  pub fn find_look_at(&self, name: &str) -> Option<&LookAtRecord> {
+    self
+      .look_at_index
+      .find(name)
+      .map(|index| &self.look_at[*index])
   }
 
-  pub fn look_many(
+  pub fn look_service(
     &mut self,
-    domains: Vec<String>,
-    ipv4: Vec<Ipv4Addr>,
-    ipv6: Vec<Ipv6Addr>,
+    name: String,
+    target: String,
+    port: u16,
+    priority: u16,
+    weight: u16,
+  ) {
+    let key: String = name.trim_end_matches('.').to_lowercase();
+    let service: LookAtService = LookAtService::new(name, target, port, priority, weight);
+    self.services.entry(key).or_default().push(service);
+  }
+
+  /// Finds the services published under a name
+  pub fn find_services(&self, name: &str) -> &[LookAtService] {
+    self
+      .services
+      .get(&name.trim_end_matches('.').to_lowercase())
+      .map_or(&[], Vec::as_slice)
+  }
+
+  pub fn look_chaos(&mut self, name: &str, value: String) {
+    self.chaos_names.insert(name.to_lowercase(), value);
+  }
+
+  pub fn look_alias(&mut self, name: &str, target: String) {
+    let name: String = name.trim_end_matches('.').to_lowercase();
+    self.aliases.insert(name, target.trim_end_matches('.').to_string());
+  }
+
+  // *
+
+  pub fn serve_zone(&mut self, zone: Zone) {
+    self.zones.insert(zone);
+  }
+
+  pub fn forward_zone(
+    &mut self,
+    zone: String,
+    nameservers: Vec<SocketAddr>,
+    mode: ResolveMode,
   ) {
-    self.__add_look_at_record(domains, ipv4, ipv6);
+    self
+      .forward_zones
+      .push(ForwardZone::new(zone, nameservers, mode));
+  }
+
+  /// Finds the most specific forwarding rule for a name (longest zone suffix)
+  pub fn find_forward_zone(&self, name: &str) -> Option<&ForwardZone> {
+    self
+      .forward_zones
+      .iter()
+      .filter(|forward_zone| in_zone(name, &forward_zone.zone))
+      .max_by_key(|forward_zone| forward_zone.zone.len())
   }
 }
@@ -0,0 +1,166 @@
+use super::{BytePacketBuffer as Buffer, EdnsOption, PACKET_BUFFER_SIZE};
+use crate::enums::{BytePacketError as Error, QueryType};
+
+// * >>> *
+
+pub const EDNS_DNSSEC_OK: u16 = 0x8000;
+pub const EDNS_BAD_VERSION: u8 = 0x01;
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct EdnsOpt {
+  pub payload_size: u16,
+  pub extended_rcode: u8,
+  pub version: u8,
+  pub flags: u16,
+  pub options: Vec<EdnsOption>,
+}
+
+impl EdnsOpt {
+  pub fn new(payload_size: u16) -> Self {
+    Self {
+      payload_size,
+      extended_rcode: 0,
+      version: 0,
+      flags: 0,
+      options: Vec::new(),
+    }
+  }
+
+  /// The OPT record answering `request`, which keeps its DO bit (RFC 3225 section 3)
+  pub fn reply(request: &EdnsOpt) -> Self {
+    let mut opt: Self = Self::new(PACKET_BUFFER_SIZE as u16);
+    opt.flags = request.flags & EDNS_DNSSEC_OK;
+    opt
+  }
+
+  // *
+
+  pub fn dnssec_ok(&self) -> bool { self.flags & EDNS_DNSSEC_OK != 0 }
+
+  // *
+
+  pub fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+    let mut domain: String = String::new();
+    buffer.read_qname(&mut domain)?;
+    buffer.read_u16()?;
+
+    let payload_size: u16 = buffer.read_u16()?;
+    let ttl: u32 = buffer.read_u32()?;
+    let data_len: usize = buffer.read_u16()? as usize;
+
+    let end: usize = buffer.pos() + data_len;
+    let mut options: Vec<EdnsOption> = Vec::new();
+    while buffer.pos() < end {
+      let code: u16 = buffer.read_u16()?;
+      let length: usize = buffer.read_u16()? as usize;
+      let data: Vec<u8> = buffer.get_range(buffer.pos(), length)?.to_vec();
+      buffer.step(length)?;
+      options.push(EdnsOption::new(code, data));
+    }
+
+    if buffer.pos() != end {
+      return Err(Error::InvalidPosition);
+    }
+
+    Ok(Self {
+      payload_size,
+      extended_rcode: (ttl >> 0x18) as u8,
+      version: ((ttl >> 0x10) & 0xFF) as u8,
+      flags: (ttl & 0xFFFF) as u16,
+      options,
+    })
+  }
+
+  // *
+
+  pub fn write(&self, buffer: &mut Buffer) -> Result<(), Error> {
+    buffer.write(0x00)?;
+
+    buffer.write_u16(QueryType::OPT.to_u16())?;
+    buffer.write_u16(self.payload_size)?;
+    buffer.write_u32(
+      ((self.extended_rcode as u32) << 0x18)
+        | ((self.version as u32) << 0x10)
+        | self.flags as u32,
+    )?;
+
+    let pos: usize = buffer.pos();
+    buffer.write_u16(0x00)?;
+    for option in &self.options {
+      buffer.write_u16(option.code)?;
+      buffer.write_u16(option.data.len() as u16)?;
+      buffer.write_bytes(&option.data)?;
+    }
+
+    let size: usize = buffer.pos() - (pos + 0x02);
+    buffer.set_u16(pos, size as u16)
+  }
+}
+
+// * >>> *
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::structs::{DnsPacket, UDP_PAYLOAD_SIZE};
+
+  fn round_trip(packet: &mut DnsPacket) -> DnsPacket {
+    let mut buffer: Buffer = Buffer::new();
+    packet.write(&mut buffer).unwrap();
+    buffer.seek(0x00).unwrap();
+    DnsPacket::from_buffer(&mut buffer).unwrap()
+  }
+
+  // *
+
+  #[test]
+  fn opt_round_trips_through_a_packet() {
+    let mut opt: EdnsOpt = EdnsOpt::new(0x1000);
+    opt.extended_rcode = EDNS_BAD_VERSION;
+    opt.flags = EDNS_DNSSEC_OK;
+    opt.options.push(EdnsOption::new(0x0A, vec![0x01, 0x02, 0x03, 0x04]));
+    opt.options.push(EdnsOption::new(0x0C, Vec::new()));
+
+    let mut packet: DnsPacket = DnsPacket::new();
+    packet.edns = Some(opt.clone());
+    let parsed: DnsPacket = round_trip(&mut packet);
+    assert_eq!(parsed.header.resource_entries, 0x01);
+    assert!(parsed.resources.is_empty());
+    assert!(parsed.edns.as_ref().unwrap().dnssec_ok());
+    assert_eq!(parsed.edns, Some(opt));
+  }
+
+  #[test]
+  fn packet_without_opt_has_no_edns() {
+    let parsed: DnsPacket = round_trip(&mut DnsPacket::new());
+    assert_eq!(parsed.edns, None);
+    assert_eq!(parsed.udp_payload_size(), UDP_PAYLOAD_SIZE);
+  }
+
+  #[test]
+  fn payload_size_is_clamped() {
+    let mut packet: DnsPacket = DnsPacket::new();
+    for (payload_size, expected) in [
+      (0x0000, UDP_PAYLOAD_SIZE),
+      (0x0100, UDP_PAYLOAD_SIZE),
+      (0x0400, 0x0400),
+      (0x1000, PACKET_BUFFER_SIZE),
+      (0xFFFF, PACKET_BUFFER_SIZE),
+    ] {
+      packet.edns = Some(EdnsOpt::new(payload_size));
+      assert_eq!(packet.udp_payload_size(), expected);
+    }
+  }
+
+  #[test]
+  fn replies_keep_only_the_dnssec_ok_flag() {
+    let mut request: EdnsOpt = EdnsOpt::new(0x0200);
+    request.flags = EDNS_DNSSEC_OK | 0x0001;
+    request.options.push(EdnsOption::new(0x0A, vec![0x01, 0x02]));
+
+    let opt: EdnsOpt = EdnsOpt::reply(&request);
+    assert_eq!(opt.flags, EDNS_DNSSEC_OK);
+    assert_eq!(opt.payload_size, PACKET_BUFFER_SIZE as u16);
+    assert!(opt.options.is_empty());
+    assert!(!EdnsOpt::reply(&EdnsOpt::new(0x0200)).dnssec_ok());
+  }
+}
@@ -0,0 +1,9 @@
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct EdnsOption {
+  pub code: u16,
+  pub data: Vec<u8>,
+}
+
+impl EdnsOption {
+  pub fn new(code: u16, data: Vec<u8>) -> Self { Self { code, data } }
+}
@@ -0,0 +1,21 @@
+use crate::enums::ResolveMode;
+use std::net::SocketAddr;
+
+// * >>> *
+
+#[derive(Debug, Clone)]
+pub struct ForwardZone {
+  pub nameservers: Vec<SocketAddr>,
+  pub mode: ResolveMode,
+  pub zone: String,
+}
+
+impl ForwardZone {
+  pub fn new(zone: String, nameservers: Vec<SocketAddr>, mode: ResolveMode) -> Self {
+    Self {
+      nameservers,
+      mode,
+      zone: zone.trim_end_matches('.').to_lowercase(),
+    }
+  }
+}
@@ -0,0 +1,40 @@
+use std::future::pending;
+
+#[cfg(unix)]
+use tokio::signal::unix::{Signal, SignalKind, signal};
+
+// * >>> *
+
+/// SIGHUP notifications of the process. On platforms without signals, or when the
+/// handler can not be installed, none ever arrives.
+#[derive(Debug)]
+pub struct HangupSignal {
+  #[cfg(unix)]
+  signal: Option<Signal>,
+}
+
+impl HangupSignal {
+  pub fn new() -> Self {
+    Self {
+      #[cfg(unix)]
+      signal: signal(SignalKind::hangup()).ok(),
+    }
+  }
+
+  // *
+
+  pub async fn recv(&mut self) {
+    #[cfg(unix)]
+    if let Some(signal) = &mut self.signal
+      && signal.recv().await.is_some()
+    {
+      return;
+    }
+
+    pending::<()>().await
+  }
+}
+
+impl Default for HangupSignal {
+  fn default() -> Self { Self::new() }
+}
@@ -1,3 +1,5 @@
+use super::LookAtSoa;
+use crate::enums::BlockMode;
 use std::net::{Ipv4Addr, Ipv6Addr};
 
 // * >>> *
@@ -6,15 +8,43 @@ use std::net::{Ipv4Addr, Ipv6Addr};
 pub struct LookAtRecord {
   pub ipv6_addrs: Vec<Ipv6Addr>,
   pub ipv4_addrs: Vec<Ipv4Addr>,
+  pub txt_records: Vec<String>,
+  pub raw_records: Vec<(u16, Vec<u8>)>,
+  pub ns_hosts: Vec<String>,
+  pub mx_hosts: Vec<(u16, String)>,
+  pub soa: Option<LookAtSoa>,
+  pub block_mode: Option<BlockMode>,
   pub domains: Vec<String>,
+  pub reverse: bool,
 }
 
 impl LookAtRecord {
+  /// Entry answering the given addresses, without other records. The remaining
+  /// fields are set afterwards, e.g. `LookAtRecord { soa, ..LookAtRecord::new(...) }`
   pub fn new(domains: Vec<String>, ipv4: Vec<Ipv4Addr>, ipv6: Vec<Ipv6Addr>) -> Self {
     Self {
       ipv6_addrs: ipv6,
       ipv4_addrs: ipv4,
+      txt_records: Vec::new(),
+      raw_records: Vec::new(),
+      ns_hosts: Vec::new(),
+      mx_hosts: Vec::new(),
+      soa: None,
+      block_mode: None,
       domains,
+      reverse: true,
     }
   }
+
+  // *
+
+  pub fn is_blocked(&self) -> bool {
+    self.txt_records.is_empty()
+      && self.raw_records.is_empty()
+      && self.ns_hosts.is_empty()
+      && self.mx_hosts.is_empty()
+      && self.soa.is_none()
+      && !self.ipv4_addrs.iter().any(|ip| ip.octets() != [0; 4])
+      && !self.ipv6_addrs.iter().any(|ip| ip.segments() != [0; 8])
+  }
 }
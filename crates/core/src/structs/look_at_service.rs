@@ -0,0 +1,26 @@
+#[derive(Debug, Clone)]
+pub struct LookAtService {
+  pub priority: u16,
+  pub weight: u16,
+  pub port: u16,
+  pub target: String,
+  pub name: String,
+}
+
+impl LookAtService {
+  pub fn new(
+    name: String,
+    target: String,
+    port: u16,
+    priority: u16,
+    weight: u16,
+  ) -> Self {
+    Self {
+      priority,
+      weight,
+      port,
+      target,
+      name,
+    }
+  }
+}
@@ -0,0 +1,24 @@
+#[derive(Debug, Clone)]
+pub struct LookAtSoa {
+  pub mname: String,
+  pub rname: String,
+  pub serial: u32,
+  pub refresh: u32,
+  pub retry: u32,
+  pub expire: u32,
+  pub minimum: u32,
+}
+
+impl LookAtSoa {
+  pub fn new(mname: String, rname: String, serial: u32, minimum: u32) -> Self {
+    Self {
+      mname,
+      rname,
+      serial,
+      refresh: 0xE10,
+      retry: 0x258,
+      expire: 0x93A80,
+      minimum,
+    }
+  }
+}
@@ -0,0 +1,51 @@
+use super::{DelegationCache, UpstreamHealth};
+use std::{
+  io::{Error, ErrorKind},
+  net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket as StdUdpSocket},
+  time::Duration,
+};
+use tokio::net::UdpSocket;
+
+// * >>> *
+
+#[derive(Debug)]
+pub struct LookupClient {
+  pub timeout: Duration,
+  pub health: UpstreamHealth,
+  pub delegations: DelegationCache,
+  ipv6: bool,
+}
+
+impl LookupClient {
+  pub fn new(timeout: Duration) -> Result<Self, Error> {
+    StdUdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+
+    // hosts without IPv6 still resolve through IPv4 upstreams
+    let ipv6: bool = StdUdpSocket::bind((Ipv6Addr::UNSPECIFIED, 0)).is_ok();
+    Ok(Self {
+      timeout,
+      health: UpstreamHealth::default(),
+      delegations: DelegationCache::default(),
+      ipv6,
+    })
+  }
+
+  // *
+
+  /// Binds a new socket on a random source port for each upstream query, so replies
+  /// cannot be guessed from previous queries or mixed up between workers.
+  pub async fn socket(&self, server: &SocketAddr) -> Result<UdpSocket, Error> {
+    let local_ip: IpAddr = match server {
+      SocketAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+      SocketAddr::V6(_) if self.ipv6 => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+      SocketAddr::V6(_) => {
+        return Err(Error::new(
+          ErrorKind::AddrNotAvailable,
+          "IPv6 lookups are not available",
+        ));
+      },
+    };
+
+    UdpSocket::bind((local_ip, 0)).await
+  }
+}
@@ -1,17 +1,56 @@
+mod blocklist;
+mod blocklist_source;
 mod byte_packet_buffer;
+mod config_reloader;
+mod delegation_cache;
+mod dns_cache;
 mod dns_header;
 mod dns_packet;
 mod dns_question;
 mod dns_server_config;
+mod edns_opt;
+mod edns_option;
+mod forward_zone;
+mod hangup_signal;
 mod look_at_record;
+mod look_at_service;
+mod look_at_soa;
+mod lookup_client;
+mod name_trie;
+mod shared_config;
+mod upstream_health;
 mod worker_task;
+mod zone;
+mod zone_store;
 
 // * >>> *
 
-pub use byte_packet_buffer::BytePacketBuffer;
+pub use blocklist::Blocklist;
+pub use blocklist_source::BlocklistSource;
+pub use byte_packet_buffer::{
+  BytePacketBuffer,
+  PACKET_BUFFER_SIZE,
+  TCP_MESSAGE_SIZE,
+  UDP_PAYLOAD_SIZE,
+};
+pub use config_reloader::ConfigReloader;
+pub use delegation_cache::DelegationCache;
+pub use dns_cache::{DnsCache, MAX_CACHE_TTL};
 pub use dns_header::DnsHeader;
 pub use dns_packet::DnsPacket;
 pub use dns_question::DnsQuestion;
 pub use dns_server_config::DnsServerConfig;
+pub use edns_opt::{EDNS_BAD_VERSION, EDNS_DNSSEC_OK, EdnsOpt};
+pub use edns_option::EdnsOption;
+pub use forward_zone::ForwardZone;
+pub use hangup_signal::HangupSignal;
 pub use look_at_record::LookAtRecord;
+pub use look_at_service::LookAtService;
+pub use look_at_soa::LookAtSoa;
+pub use lookup_client::LookupClient;
+pub use name_trie::NameTrie;
+pub use shared_config::SharedConfig;
+pub use upstream_health::{QUARANTINE_TIME, UpstreamHealth};
 pub use worker_task::WorkerTask;
+pub use zone::Zone;
+pub use zone_store::ZoneStore;
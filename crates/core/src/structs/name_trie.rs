@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+// * >>> *
+
+#[derive(Debug, Clone)]
+struct NameNode<T> {
+  children: HashMap<String, NameNode<T>>,
+  value: Option<T>,
+}
+
+impl<T> NameNode<T> {
+  fn new() -> Self {
+    Self {
+      children: HashMap::new(),
+      value: None,
+    }
+  }
+}
+
+// *
+
+/// Domain names stored label by label from the root down ("www.example.com" is
+/// com -> example -> www), so lookups take one step per label of the asked name
+#[derive(Debug, Clone)]
+pub struct NameTrie<T> {
+  root: NameNode<T>,
+}
+
+impl<T> NameTrie<T> {
+  pub fn new() -> Self {
+    Self {
+      root: NameNode::new(),
+    }
+  }
+
+  // *
+
+  /// Adds a value for a name, keeping the value already there if any
+  pub fn insert(&mut self, name: &str, value: T) {
+    let name: String = name.trim_end_matches('.').to_lowercase();
+    let mut node: &mut NameNode<T> = &mut self.root;
+    for label in name.rsplit('.').filter(|label| !label.is_empty()) {
+      node = node
+        .children
+        .entry(label.to_string())
+        .or_insert_with(NameNode::new);
+    }
+    node.value.get_or_insert(value);
+  }
+
+  /// Finds the value of exactly that name
+  pub fn get(&self, name: &str) -> Option<&T> {
+    let name: String = name.trim_end_matches('.').to_lowercase();
+    let mut node: &NameNode<T> = &self.root;
+    for label in name.rsplit('.').filter(|label| !label.is_empty()) {
+      node = node.children.get(label)?;
+    }
+    node.value.as_ref()
+  }
+
+  /// Finds the value of a name, or else of the wildcard ("*.<name>") at its closest
+  /// encloser: the deepest node on its path, as every node is a name that exists or
+  /// has names below it (RFC 4592 section 3.3.1)
+  pub fn find(&self, name: &str) -> Option<&T> {
+    let name: String = name.trim_end_matches('.').to_lowercase();
+    let mut node: &NameNode<T> = &self.root;
+    for label in name.rsplit('.').filter(|label| !label.is_empty()) {
+      match node.children.get(label) {
+        Some(child) => node = child,
+        // a wildcard never covers names that exist, nor names below them
+        None => return node.children.get("*")?.value.as_ref(),
+      }
+    }
+
+    node.value.as_ref()
+  }
+
+  /// Finds the value of the closest ancestor of a name (suffix matching), not
+  /// counting the name itself
+  pub fn find_ancestor(&self, name: &str) -> Option<&T> {
+    let name: String = name.trim_end_matches('.').to_lowercase();
+    let mut found: Option<&T> = None;
+    let mut node: &NameNode<T> = &self.root;
+    for label in name.rsplit('.').filter(|label| !label.is_empty()) {
+      found = node.value.as_ref().or(found);
+      match node.children.get(label) {
+        Some(child) => node = child,
+        None => break,
+      }
+    }
+
+    found
+  }
+}
+
+impl<T> Default for NameTrie<T> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+// * >>> *
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn trie(names: &[&'static str]) -> NameTrie<&'static str> {
+    let mut trie: NameTrie<&'static str> = NameTrie::new();
+    for name in names {
+      trie.insert(name, name);
+    }
+    trie
+  }
+
+  // *
+
+  #[test]
+  fn find_prefers_the_exact_name() {
+    let trie: NameTrie<&str> = trie(&["*.example.com", "www.example.com"]);
+    assert_eq!(trie.find("www.example.com"), Some(&"www.example.com"));
+    assert_eq!(trie.find("WWW.Example.com."), Some(&"www.example.com"));
+    assert_eq!(trie.find("mail.example.com"), Some(&"*.example.com"));
+    assert_eq!(trie.find("a.b.example.com"), Some(&"*.example.com"));
+    // the wildcard does not cover its parent
+    assert_eq!(trie.find("example.com"), None);
+  }
+
+  #[test]
+  fn find_never_uses_a_wildcard_across_existing_names() {
+    let trie: NameTrie<&str> =
+      trie(&["*.example.com", "host.example.com", "a.sub.example.com"]);
+    // names below an existing name are not covered by the wildcard above it
+    assert_eq!(trie.find("x.host.example.com"), None);
+    // "sub" exists as an empty non-terminal, so it hides the wildcard as well
+    assert_eq!(trie.find("sub.example.com"), None);
+    assert_eq!(trie.find("b.sub.example.com"), None);
+    assert_eq!(trie.find("other.example.com"), Some(&"*.example.com"));
+  }
+
+  #[test]
+  fn find_uses_the_wildcard_at_the_closest_encloser() {
+    let trie: NameTrie<&str> =
+      trie(&["*.example.com", "*.sub.example.com", "a.sub.example.com"]);
+    assert_eq!(trie.find("b.sub.example.com"), Some(&"*.sub.example.com"));
+    assert_eq!(trie.find("x.y.sub.example.com"), Some(&"*.sub.example.com"));
+    assert_eq!(trie.find("a.sub.example.com"), Some(&"a.sub.example.com"));
+    assert_eq!(trie.find("c.example.com"), Some(&"*.example.com"));
+  }
+
+  #[test]
+  fn insert_keeps_the_first_value() {
+    let mut trie: NameTrie<u8> = NameTrie::new();
+    trie.insert("example.com", 0x01);
+    trie.insert("Example.com.", 0x02);
+    assert_eq!(trie.find("example.com"), Some(&0x01));
+  }
+
+  #[test]
+  fn get_ignores_wildcards() {
+    let trie: NameTrie<&str> = trie(&["*.example.com"]);
+    assert_eq!(trie.get("www.example.com"), None);
+    assert_eq!(trie.get("*.example.com"), Some(&"*.example.com"));
+  }
+
+  #[test]
+  fn find_ancestor_skips_the_name_itself() {
+    let trie: NameTrie<&str> = trie(&["example.com", "sub.example.com"]);
+    assert_eq!(trie.find_ancestor("sub.example.com"), Some(&"example.com"));
+    assert_eq!(trie.find_ancestor("a.sub.example.com"), Some(&"sub.example.com"));
+    assert_eq!(trie.find_ancestor("a.b.sub.example.com"), Some(&"sub.example.com"));
+    assert_eq!(trie.find_ancestor("x.example.com"), Some(&"example.com"));
+    assert_eq!(trie.find_ancestor("example.com"), None);
+    assert_eq!(trie.find_ancestor("example.org"), None);
+  }
+
+  #[test]
+  fn find_ancestor_passes_empty_non_terminals() {
+    let trie: NameTrie<&str> = trie(&["example.com", "a.b.example.com"]);
+    // "b.example.com" has no value of its own
+    assert_eq!(trie.find_ancestor("x.b.example.com"), Some(&"example.com"));
+    assert_eq!(trie.find_ancestor("b.example.com"), Some(&"example.com"));
+  }
+}
@@ -0,0 +1,70 @@
+use super::DnsServerConfig;
+use std::sync::Arc;
+use tokio::sync::watch;
+
+// * >>> *
+
+/// The configuration queries are answered with. Storing a new one swaps it in for the
+/// queries received next, while those in flight finish with the one they took. Clones
+/// share the same configuration.
+#[derive(Debug, Clone)]
+pub struct SharedConfig {
+  current: Arc<watch::Sender<Arc<DnsServerConfig>>>,
+}
+
+impl SharedConfig {
+  pub fn new(config: DnsServerConfig) -> Self {
+    let (current, _) = watch::channel(Arc::new(config));
+    Self {
+      current: Arc::new(current),
+    }
+  }
+
+  // *
+
+  pub fn load(&self) -> Arc<DnsServerConfig> {
+    Arc::clone(&self.current.borrow())
+  }
+
+  pub fn store(&self, config: DnsServerConfig) {
+    self.current.send_replace(Arc::new(config));
+  }
+
+  /// Receiver notified every time a new configuration is stored
+  pub fn subscribe(&self) -> watch::Receiver<Arc<DnsServerConfig>> {
+    self.current.subscribe()
+  }
+}
+
+// * >>> *
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::scalable_block;
+  use std::net::{Ipv4Addr, SocketAddr};
+
+  fn config(last_octet: u8) -> DnsServerConfig {
+    let nameservers: Vec<SocketAddr> =
+      vec![SocketAddr::from((Ipv4Addr::new(192, 0, 2, last_octet), 53))];
+    scalable_block!({ DnsServerConfig::new(nameservers, 0x01, 0x01) }, {
+      DnsServerConfig::new(nameservers)
+    })
+  }
+
+  // *
+
+  #[test]
+  fn stored_configuration_is_seen_by_new_loads() {
+    let shared_config: SharedConfig = SharedConfig::new(config(0x01));
+    let clone: SharedConfig = shared_config.clone();
+    let mut changes: watch::Receiver<Arc<DnsServerConfig>> = clone.subscribe();
+    let in_flight: Arc<DnsServerConfig> = shared_config.load();
+
+    shared_config.store(config(0x02));
+    assert!(changes.has_changed().unwrap());
+    assert_eq!(changes.borrow_and_update().nameservers, config(0x02).nameservers);
+    assert_eq!(clone.load().nameservers, config(0x02).nameservers);
+    assert_eq!(in_flight.nameservers, config(0x01).nameservers);
+  }
+}
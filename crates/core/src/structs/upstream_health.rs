@@ -0,0 +1,220 @@
+use std::{
+  collections::HashMap,
+  net::SocketAddr,
+  sync::Mutex,
+  time::{Duration, Instant},
+};
+
+// * >>> *
+
+pub const QUARANTINE_TIME: Duration = Duration::from_secs(30);
+const MAX_FAILURES: u32 = 0x03;
+const MAX_SRTT: Duration = Duration::from_secs(10);
+const MIN_FAILURE_SRTT: Duration = Duration::from_millis(100);
+const SRTT_SAMPLE_WEIGHT: f64 = 0.3;
+const SRTT_UNUSED_DECAY: f64 = 0.98;
+const MAX_SERVERS: usize = 0x1000;
+const IDLE_TIME: Duration = Duration::from_secs(600);
+
+#[derive(Debug, Clone)]
+struct ServerStats {
+  srtt: Duration,
+  failures: u32,
+  quarantined_until: Option<Instant>,
+  last_used: Instant,
+}
+
+impl ServerStats {
+  fn new(now: Instant) -> Self {
+    // unknown servers start with a small random SRTT so all of them get tried
+    Self {
+      srtt: Duration::from_micros(rand::random_range(0..32_000)),
+      failures: 0x00,
+      quarantined_until: None,
+      last_used: now,
+    }
+  }
+
+  fn is_quarantined(&self, now: Instant) -> bool {
+    self.quarantined_until.is_some_and(|until| until > now)
+  }
+
+  /// Unused for so long that its SRTT has decayed away and its quarantine is over
+  fn is_idle(&self, now: Instant) -> bool {
+    now.duration_since(self.last_used) > IDLE_TIME && !self.is_quarantined(now)
+  }
+}
+
+// *
+
+/// Tracks round-trip times and failures of upstream servers (smoothed RTT selection)
+#[derive(Debug, Default)]
+pub struct UpstreamHealth {
+  servers: Mutex<HashMap<SocketAddr, ServerStats>>,
+}
+
+impl UpstreamHealth {
+  /// Orders servers fastest first; quarantined servers are only tried as a last resort
+  pub fn order(&self, servers: &[SocketAddr]) -> Vec<SocketAddr> {
+    let Ok(mut stats) = self.servers.lock() else {
+      return servers.to_vec();
+    };
+    let now: Instant = Instant::now();
+
+    let mut ordered: Vec<(bool, Duration, SocketAddr)> = servers
+      .iter()
+      .map(|server| {
+        let entry: &ServerStats = server_entry(&mut stats, *server, now);
+        (entry.is_quarantined(now), entry.srtt, *server)
+      })
+      .collect();
+    ordered.sort();
+
+    // servers that are not picked slowly look faster, so they are retried later on
+    for (_, _, server) in ordered.iter().skip(0x01) {
+      if let Some(entry) = stats.get_mut(server) {
+        entry.srtt = entry.srtt.mul_f64(SRTT_UNUSED_DECAY);
+      }
+    }
+
+    ordered.into_iter().map(|(_, _, server)| server).collect()
+  }
+
+  // *
+
+  pub fn record_success(&self, server: SocketAddr, rtt: Duration) {
+    let Ok(mut stats) = self.servers.lock() else {
+      return;
+    };
+
+    let entry: &mut ServerStats = server_entry(&mut stats, server, Instant::now());
+    entry.srtt = entry
+      .srtt
+      .mul_f64(0x01 as f64 - SRTT_SAMPLE_WEIGHT)
+      .saturating_add(rtt.mul_f64(SRTT_SAMPLE_WEIGHT));
+    entry.failures = 0x00;
+    entry.quarantined_until = None;
+  }
+
+  pub fn record_failure(&self, server: SocketAddr) {
+    let Ok(mut stats) = self.servers.lock() else {
+      return;
+    };
+
+    let now: Instant = Instant::now();
+    let entry: &mut ServerStats = server_entry(&mut stats, server, now);
+    entry.srtt = entry.srtt.saturating_mul(0x02).clamp(MIN_FAILURE_SRTT, MAX_SRTT);
+    entry.failures += 0x01;
+    if entry.failures >= MAX_FAILURES {
+      entry.quarantined_until = Some(now + QUARANTINE_TIME);
+    }
+  }
+}
+
+// *
+
+fn server_entry(
+  stats: &mut HashMap<SocketAddr, ServerStats>,
+  server: SocketAddr,
+  now: Instant,
+) -> &mut ServerStats {
+  // the table is bounded: idle servers are dropped first, then the least recently used
+  if !stats.contains_key(&server) && stats.len() >= MAX_SERVERS {
+    stats.retain(|_, entry| !entry.is_idle(now));
+    if stats.len() >= MAX_SERVERS
+      && let Some(oldest) = stats
+        .iter()
+        .min_by_key(|(_, entry)| entry.last_used)
+        .map(|(server, _)| *server)
+    {
+      stats.remove(&oldest);
+    }
+  }
+
+  let entry: &mut ServerStats =
+    stats.entry(server).or_insert_with(|| ServerStats::new(now));
+  entry.last_used = now;
+  entry
+}
+
+// * >>> *
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::net::Ipv4Addr;
+
+  fn server(last_octet: u8) -> SocketAddr {
+    SocketAddr::from((Ipv4Addr::new(0xC0, 0x00, 0x02, last_octet), 53))
+  }
+
+  fn is_quarantined(health: &UpstreamHealth, server: SocketAddr) -> bool {
+    health.servers.lock().unwrap()[&server].is_quarantined(Instant::now())
+  }
+
+  // *
+
+  #[test]
+  fn faster_servers_come_first() {
+    let health: UpstreamHealth = UpstreamHealth::default();
+    for _ in 0..0x0A {
+      health.record_success(server(0x01), Duration::from_millis(200));
+      health.record_success(server(0x02), Duration::from_millis(5));
+    }
+
+    assert_eq!(health.order(&[server(0x01), server(0x02)]), [server(0x02), server(0x01)]);
+    // servers never used before are tried ahead of slow ones
+    let order: Vec<SocketAddr> = health.order(&[server(0x01), server(0x03)]);
+    assert_eq!(order, [server(0x03), server(0x01)]);
+  }
+
+  #[test]
+  fn failing_servers_are_quarantined() {
+    let health: UpstreamHealth = UpstreamHealth::default();
+    health.record_success(server(0x02), Duration::from_secs(5));
+    for _ in 0..MAX_FAILURES - 0x01 {
+      health.record_failure(server(0x01));
+    }
+    assert!(!is_quarantined(&health, server(0x01)));
+
+    health.record_failure(server(0x01));
+    assert!(is_quarantined(&health, server(0x01)));
+    // quarantined servers go last, even behind slow ones
+    assert_eq!(health.order(&[server(0x01), server(0x02)]), [server(0x02), server(0x01)]);
+
+    // one answer is enough to take a server out of quarantine
+    health.record_success(server(0x01), Duration::from_millis(1));
+    assert!(!is_quarantined(&health, server(0x01)));
+    assert_eq!(health.order(&[server(0x01), server(0x02)]), [server(0x01), server(0x02)]);
+  }
+
+  #[test]
+  fn table_drops_idle_then_least_recently_used_servers() {
+    let mut stats: HashMap<SocketAddr, ServerStats> = HashMap::new();
+    let servers: Vec<SocketAddr> = (0..MAX_SERVERS as u32)
+      .map(|i| SocketAddr::from((Ipv4Addr::from(0x0A000000 + i), 53)))
+      .collect();
+
+    // every server was used a moment ago, but one that has been idle for long
+    let base: Instant = Instant::now();
+    let recent: Instant = base + IDLE_TIME * 0x02;
+    for (i, server) in servers.iter().enumerate() {
+      let used: Instant = match i {
+        0x10 => base,
+        _ => recent + Duration::from_millis(i as u64),
+      };
+      server_entry(&mut stats, *server, used);
+    }
+
+    let now: Instant = recent + Duration::from_secs(5);
+    server_entry(&mut stats, server(0x01), now);
+    assert_eq!(stats.len(), MAX_SERVERS);
+    assert!(!stats.contains_key(&servers[0x10]));
+    assert!(stats.contains_key(&servers[0x00]));
+
+    server_entry(&mut stats, server(0x02), now);
+    assert_eq!(stats.len(), MAX_SERVERS);
+    assert!(!stats.contains_key(&servers[0x00]));
+    assert!(stats.contains_key(&server(0x01)));
+  }
+}
@@ -1,16 +1,15 @@
-use super::DnsServerConfig;
-use std::{
-  net::{SocketAddr, UdpSocket},
-  sync::Arc,
-};
+use super::{DnsCache, DnsServerConfig, LookupClient};
+use std::{net::SocketAddr, sync::Arc};
+use tokio::net::UdpSocket;
 
 // * >>> *
 
 #[derive(Debug)]
 pub struct WorkerTask {
   pub socket: Arc<UdpSocket>,
-  pub lookup: Arc<UdpSocket>,
+  pub lookup: Arc<LookupClient>,
   pub config: Arc<DnsServerConfig>,
+  pub cache: Arc<DnsCache>,
   pub data: Vec<u8>,
   pub src: SocketAddr,
   pub debug: bool,
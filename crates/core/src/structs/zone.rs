@@ -0,0 +1,95 @@
+use crate::enums::{DnsRecord, QueryType};
+use std::collections::{HashMap, HashSet};
+
+// * >>> *
+
+/// Records of one authoritative zone, grouped by owner name (lowercase, no trailing dot)
+#[derive(Debug, Clone)]
+pub struct Zone {
+  pub origin: String,
+  pub records: HashMap<String, Vec<DnsRecord>>,
+  /// Names with other names below them, so empty non-terminals are found without
+  /// going through every owner name
+  parents: HashSet<String>,
+}
+
+impl Zone {
+  pub fn new(origin: &str) -> Self {
+    Self {
+      origin: origin.trim_end_matches('.').to_lowercase(),
+      records: HashMap::new(),
+      parents: HashSet::new(),
+    }
+  }
+
+  // *
+
+  pub fn insert(&mut self, record: DnsRecord) {
+    let owner: String = record.domain().trim_end_matches('.').to_lowercase();
+    let mut name: &str = &owner;
+    while let Some((_, parent)) = name.split_once('.') {
+      if !self.parents.insert(parent.to_string()) {
+        break;
+      }
+      name = parent;
+    }
+
+    let records: &mut Vec<DnsRecord> = self.records.entry(owner).or_default();
+    if !records.contains(&record) {
+      records.push(record);
+    }
+  }
+
+  pub fn lookup(&self, name: &str) -> Option<&[DnsRecord]> {
+    let name: String = name.trim_end_matches('.').to_lowercase();
+    self.records.get(&name).map(Vec::as_slice)
+  }
+
+  pub fn rrset(&self, name: &str, qtype: QueryType) -> Vec<DnsRecord> {
+    self
+      .lookup(name)
+      .unwrap_or_default()
+      .iter()
+      .filter(|record| record.qtype() == qtype)
+      .cloned()
+      .collect()
+  }
+
+  pub fn soa(&self) -> Option<DnsRecord> {
+    self.rrset(&self.origin, QueryType::SOA).into_iter().next()
+  }
+
+  /// Checks whether a name without records of its own has names below it (an empty
+  /// non-terminal), which makes it exist in the zone
+  pub fn has_descendants(&self, name: &str) -> bool {
+    let name: String = name.trim_end_matches('.').to_lowercase();
+    self.parents.contains(&name)
+  }
+}
+
+// * >>> *
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::enums::QueryClass;
+  use std::net::Ipv4Addr;
+
+  #[test]
+  fn names_above_owners_are_empty_non_terminals() {
+    let mut zone: Zone = Zone::new("example.test.");
+    zone.insert(DnsRecord::A {
+      address: Ipv4Addr::new(192, 0, 2, 1),
+      domain: "a.b.example.test".to_string(),
+      class: QueryClass::IN,
+      ttl: 300,
+    });
+
+    assert!(zone.has_descendants("b.example.test"));
+    assert!(zone.has_descendants("B.Example.test."));
+    assert!(zone.has_descendants("example.test"));
+    assert!(zone.lookup("b.example.test").is_none());
+    assert!(!zone.has_descendants("a.b.example.test"));
+    assert!(!zone.has_descendants("c.example.test"));
+  }
+}
@@ -0,0 +1,39 @@
+use super::Zone;
+use std::collections::HashMap;
+
+// * >>> *
+
+/// In-memory store of the zones served authoritatively, keyed by origin
+#[derive(Debug, Clone, Default)]
+pub struct ZoneStore {
+  pub zones: HashMap<String, Zone>,
+}
+
+impl ZoneStore {
+  pub fn new() -> Self {
+    Self {
+      zones: HashMap::new(),
+    }
+  }
+
+  // *
+
+  pub fn insert(&mut self, zone: Zone) {
+    self.zones.insert(zone.origin.clone(), zone);
+  }
+
+  /// Finds the closest zone enclosing a name, walking up one label at a time
+  pub fn find(&self, name: &str) -> Option<&Zone> {
+    let name: String = name.trim_end_matches('.').to_lowercase();
+    let mut suffix: &str = &name;
+    loop {
+      if let Some(zone) = self.zones.get(suffix) {
+        return Some(zone);
+      }
+      if suffix.is_empty() {
+        return None;
+      }
+      suffix = suffix.split_once('.').map_or("", |(_, parent)| parent);
+    }
+  }
+}
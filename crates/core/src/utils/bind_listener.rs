@@ -0,0 +1,40 @@
+use socket2::{Domain, Protocol, Socket, Type};
+use std::{
+  io::Error,
+  net::{SocketAddr, TcpListener, UdpSocket},
+};
+
+// * >>> *
+
+fn bind_socket(
+  addr: SocketAddr,
+  kind: Type,
+  protocol: Protocol,
+) -> Result<Socket, Error> {
+  let socket: Socket = Socket::new(Domain::for_address(addr), kind, Some(protocol))?;
+
+  // IPv6 sockets only take IPv6 traffic, so '[::]' and '0.0.0.0' can be used together
+  if addr.is_ipv6() {
+    socket.set_only_v6(true)?;
+  }
+
+  // a restarted server can listen again while connections of the previous one are in
+  // TIME_WAIT; UDP sockets do not need it, and it would let another process share
+  // the port on some systems
+  if kind == Type::STREAM {
+    socket.set_reuse_address(true)?;
+  }
+  socket.bind(&addr.into())?;
+  socket.set_nonblocking(true)?;
+  Ok(socket)
+}
+
+// *
+
+pub(crate) fn bind_listener(addr: SocketAddr) -> Result<(UdpSocket, TcpListener), Error> {
+  let udp_socket: Socket = bind_socket(addr, Type::DGRAM, Protocol::UDP)?;
+  let tcp_socket: Socket = bind_socket(addr, Type::STREAM, Protocol::TCP)?;
+  tcp_socket.listen(0x400)?;
+
+  Ok((udp_socket.into(), tcp_socket.into()))
+}
@@ -0,0 +1,35 @@
+use crate::enums::{DnsRecord, QueryType};
+
+// * >>> *
+
+pub const MAX_CNAME_CHAIN: usize = 0x08;
+
+/// Walks the CNAME chain of an answer section and returns the name it stops at, when
+/// that name has no record of the queried type
+pub fn cname_target(
+  answers: &[DnsRecord],
+  qname: &str,
+  qtype: QueryType,
+) -> Option<String> {
+  let mut name: &str = qname;
+  for _ in 0..=MAX_CNAME_CHAIN {
+    if answers
+      .iter()
+      .any(|record| record.qtype() == qtype && record.domain().eq_ignore_ascii_case(name))
+    {
+      return None;
+    }
+
+    match answers.iter().find_map(|record| match record {
+      DnsRecord::CNAME { domain, host, .. } if domain.eq_ignore_ascii_case(name) => {
+        Some(host.as_str())
+      },
+      _ => None,
+    }) {
+      Some(host) => name = host,
+      None => break,
+    }
+  }
+
+  (!name.eq_ignore_ascii_case(qname)).then(|| name.to_string())
+}
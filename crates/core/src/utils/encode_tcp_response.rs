@@ -0,0 +1,19 @@
+use crate::{
+  enums::BytePacketError as Error,
+  structs::{BytePacketBuffer as Buffer, DnsPacket, TCP_MESSAGE_SIZE},
+};
+
+// * >>> *
+
+/// Encodes a response with its two byte length prefix. TCP messages can hold up to
+/// 65535 bytes, so only answers larger than that lose records.
+pub fn encode_tcp_response(response: &mut DnsPacket) -> Result<Vec<u8>, Error> {
+  let mut res_buffer: Buffer = Buffer::growable(TCP_MESSAGE_SIZE);
+  response.write_truncated(&mut res_buffer, TCP_MESSAGE_SIZE)?;
+
+  let data: &[u8] = res_buffer.get_range(0, res_buffer.pos())?;
+  let mut frame: Vec<u8> = Vec::with_capacity(data.len() + 0x02);
+  frame.extend_from_slice(&(data.len() as u16).to_be_bytes());
+  frame.extend_from_slice(data);
+  Ok(frame)
+}
@@ -1,7 +1,15 @@
 use crate::{
-  actions::build_dns_packet,
-  enums::ResultCode,
-  structs::{DnsPacket, DnsQuestion, DnsServerConfig},
+  actions::{
+    build_block_packet,
+    build_chaos_packet,
+    build_cname_packet,
+    build_dns_packet,
+    build_ptr_packet,
+    build_srv_packet,
+    build_zone_packet,
+  },
+  enums::{BlockMode, QueryClass, QueryType, ResultCode},
+  structs::{DnsPacket, DnsQuestion, DnsServerConfig, LookAtRecord, LookAtService},
 };
 
 // * >>> *
@@ -14,41 +22,114 @@ pub fn handle_look_at(
 ) -> Option<DnsPacket> {
   let req_domain: String = question.name.to_lowercase();
 
-  for record in &config.look_at {
-    let matches_domain: bool = record
-      .domains
-      .iter()
-      .map(|d| d.trim_end_matches('.').to_lowercase())
-      .any(|d| req_domain == d || req_domain.ends_with(&format!(".{}", d)));
-
-    if !matches_domain {
-      continue;
+  // CHAOS class queries are never forwarded, only identity names are answered
+  if question.qclass == QueryClass::CH {
+    if matches!(question.qtype, QueryType::TXT | QueryType::UNKNOWN(0xFF))
+      && let Some(value) = config.chaos_names.get(req_domain.trim_end_matches('.'))
+      && let Ok(result) = build_chaos_packet(&question.name, value)
+    {
+      return Some(prepare_result(result, question, response));
     }
 
-    // system to block domains
-    if !record.ipv4_addrs.iter().any(|ip| ip.octets() != [0; 4])
-      && !record.ipv6_addrs.iter().any(|ip| ip.segments() != [0; 8])
-    {
-      if debug {
-        println!(
-          "[DEBUG]: Blocked request: {} (Domain Blocking System)",
-          question.name
-        )
-      }
+    response.questions.push(question.clone());
+    response.header.rescode = ResultCode::Refused;
+    return Some(response.clone());
+  }
 
-      response.header.rescode = ResultCode::Refused;
-      return Some(response.clone());
+  if !matches!(question.qclass, QueryClass::IN | QueryClass::ANY) {
+    return None;
+  }
+
+  // blocklists apply to every name, local or not, unless an exception allows it
+  if let Some(blocklist) = &config.blocklist
+    && blocklist.is_blocked(&req_domain)
+  {
+    let mode: Option<&BlockMode> = blocklist.block_mode.as_ref();
+    return Some(blocked_response(config, question, response, mode, "Blocklist", debug));
+  }
+
+  // names inside a served zone are answered from the zone data alone
+  if let Some(zone) = config.zones.find(&req_domain)
+    && let Ok(result) = build_zone_packet(zone, question)
+  {
+    if debug {
+      println!("[DEBUG]: Zone answer: {} (zone {})", question.name, zone.origin);
     }
+    return Some(prepare_result(result, question, response));
+  }
 
-    if let Ok(mut result) = build_dns_packet(record) {
-      result.questions.push(question.clone());
-      result.header.id = response.header.id;
-      result.header.recursion_desired = response.header.recursion_desired;
-      result.header.recursion_available = response.header.recursion_available;
-      result.header.response = response.header.response;
-      return Some(result);
+  if question.qtype == QueryType::SRV {
+    let services: &[LookAtService] = config.find_services(&req_domain);
+    if !services.is_empty()
+      && let Ok(result) = build_srv_packet(services, config)
+    {
+      return Some(prepare_result(result, question, response));
     }
   }
 
-  None
+  if question.qtype == QueryType::PTR
+    && let Some(hosts) = config.reverse_names.get(req_domain.trim_end_matches('.'))
+    && let Ok(result) = build_ptr_packet(&question.name, hosts)
+  {
+    return Some(prepare_result(result, question, response));
+  }
+
+  // an alias owns no other data, its target is resolved by following the CNAME
+  if let Some(target) = config.aliases.get(req_domain.trim_end_matches('.'))
+    && let Ok(result) = build_cname_packet(&question.name, target)
+  {
+    return Some(prepare_result(result, question, response));
+  }
+
+  let record: &LookAtRecord = config.find_look_at(&req_domain)?;
+
+  // system to block domains
+  if record.is_blocked() {
+    let mode: Option<&BlockMode> = record.block_mode.as_ref();
+    let system: &str = "Domain Blocking System";
+    return Some(blocked_response(config, question, response, mode, system, debug));
+  }
+
+  build_dns_packet(record, question)
+    .ok()
+    .map(|result| prepare_result(result, question, response))
+}
+
+// *
+
+/// Answers a blocked name as its list or entry asks, or as the server default
+fn blocked_response(
+  config: &DnsServerConfig,
+  question: &DnsQuestion,
+  response: &DnsPacket,
+  mode: Option<&BlockMode>,
+  system: &str,
+  debug: bool,
+) -> DnsPacket {
+  if debug {
+    println!("[DEBUG]: Blocked request: {} ({})", question.name, system)
+  }
+
+  let mode: &BlockMode = mode.unwrap_or(&config.block_mode);
+  match build_block_packet(question, mode, config.block_ttl) {
+    Ok(result) => prepare_result(result, question, response),
+    Err(_) => {
+      let mut response: DnsPacket = response.clone();
+      response.header.rescode = ResultCode::Refused;
+      response
+    },
+  }
+}
+
+fn prepare_result(
+  mut result: DnsPacket,
+  question: &DnsQuestion,
+  response: &DnsPacket,
+) -> DnsPacket {
+  result.questions.push(question.clone());
+  result.header.id = response.header.id;
+  result.header.recursion_desired = response.header.recursion_desired;
+  result.header.recursion_available = response.header.recursion_available;
+  result.header.response = response.header.response;
+  result
 }
@@ -0,0 +1,6 @@
+/// Checks whether a name is the zone apex or below it. The root zone is empty.
+pub fn in_zone(name: &str, zone: &str) -> bool {
+  let name: String = name.trim_end_matches('.').to_lowercase();
+  let zone: String = zone.trim_end_matches('.').to_lowercase();
+  zone.is_empty() || name == zone || name.ends_with(&format!(".{}", zone))
+}
@@ -4,7 +4,7 @@ use std::net::SocketAddr;
 // * >>> *
 
 pub(crate) fn initial_message(
-  bind_addr: SocketAddr,
+  bind_addrs: &[SocketAddr],
   max_workers: usize,
   max_messages: usize,
   debug: bool,
@@ -19,12 +19,16 @@ pub(crate) fn initial_message(
 
     ( Configure the server by editing 'config.toml' )
 
-    Initializing the '{}' DNS server on the IP: {}
+    Initializing the '{}' DNS server on: {} (UDP/TCP)
     DEBUG Mode: {}
     SCALE Mode: {} (Max Workers: {} | Max Messages Queue: {})
     "#,
     legacy_code!({ "LEGACY" }, { "EXPERIMENTAL" }),
-    bind_addr,
+    bind_addrs
+      .iter()
+      .map(SocketAddr::to_string)
+      .collect::<Vec<String>>()
+      .join(", "),
     if debug { "Enabled" } else { "Disbaled" },
     if cfg!(feature = "scalability") {
       "Enabled"
@@ -1,9 +1,27 @@
+mod bind_listener;
+mod cname_target;
+mod encode_tcp_response;
 mod handle_look_at;
+mod in_zone;
 mod initial_message;
+mod parse_blocklist;
+mod parse_generic_record;
+mod parse_root_hints;
+mod parse_zone_file;
+mod reverse_name;
 mod send_response;
 
 // * >>> *
 
+pub(crate) use bind_listener::bind_listener;
+pub use cname_target::{MAX_CNAME_CHAIN, cname_target};
+pub use encode_tcp_response::encode_tcp_response;
 pub use handle_look_at::handle_look_at;
+pub use in_zone::in_zone;
 pub(crate) use initial_message::initial_message;
+pub use parse_blocklist::parse_blocklist;
+pub use parse_generic_record::parse_generic_record;
+pub use parse_root_hints::{ROOT_HINTS, parse_root_hints};
+pub use parse_zone_file::{MAX_INCLUDE_DEPTH, parse_zone_file};
+pub use reverse_name::reverse_name;
 pub use send_response::send_response;
@@ -0,0 +1,183 @@
+use crate::structs::Blocklist;
+use std::net::IpAddr;
+
+// * >>> *
+
+/// Adds the rules of a list to a blocklist, as exceptions when `allow` is set. Each
+/// line is one of:
+/// - hosts file: "0.0.0.0 ads.example.com" (only unspecified and loopback addresses)
+/// - AdGuard/ABP: "||example.com^" covers subdomains, "@@||example.com^" allows
+/// - plain: "ads.example.com", or "*.example.com" for the subdomains only
+///
+/// Comments ('#', '!'), list headers and rules that do not apply to DNS are skipped.
+/// Returns the number of rules added.
+pub fn parse_blocklist(text: &str, blocklist: &mut Blocklist, allow: bool) -> usize {
+  let mut rules: usize = 0;
+  let mut add = |name: &str, names: bool, subdomains: bool, exception: bool| {
+    if !is_domain(name) {
+      return;
+    }
+
+    type Rule = fn(&mut Blocklist, &str);
+    let (name_rule, subdomain_rule): (Rule, Rule) = match allow || exception {
+      true => (Blocklist::allow, Blocklist::allow_subdomains),
+      false => (Blocklist::block, Blocklist::block_subdomains),
+    };
+    if names {
+      name_rule(blocklist, name);
+    }
+    if subdomains {
+      subdomain_rule(blocklist, name);
+    }
+    rules += 0x01;
+  };
+
+  for line in text.lines() {
+    let line: &str = line.trim();
+    if line.is_empty() || line.starts_with(['!', '#', '[']) {
+      continue;
+    }
+
+    // AdGuard/ABP network rules, without modifiers or paths
+    let (exception, rule): (bool, &str) = match line.strip_prefix("@@") {
+      Some(rule) => (true, rule),
+      None => (false, line),
+    };
+    if let Some(rule) = rule.strip_prefix("||") {
+      if let Some(name) = rule.strip_suffix('^').or(rule.strip_suffix("^|")) {
+        add(name, true, true, exception);
+      }
+      continue;
+    }
+    // cosmetic (element hiding) rules and other exceptions
+    if exception || line.contains("##") || line.contains("#@#") || line.contains("#?#") {
+      continue;
+    }
+
+    let line: &str = line.split('#').next().unwrap_or_default();
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    match parts.as_slice() {
+      [address, names @ ..] if !names.is_empty() => {
+        let Ok(address) = address.parse::<IpAddr>() else {
+          continue;
+        };
+        if !address.is_unspecified() && !address.is_loopback() {
+          continue;
+        }
+        // hosts files also name the machine itself ("localhost", "broadcasthost")
+        for name in names.iter().filter(|name| name.contains('.')) {
+          add(name, true, false, false);
+        }
+      },
+      // a wildcard covers the names below, not the name itself
+      [name] => match name.strip_prefix("*.") {
+        Some(name) => add(name, false, true, false),
+        None => add(name, true, false, false),
+      },
+      _ => {},
+    }
+  }
+
+  rules
+}
+
+// *
+
+fn is_domain(name: &str) -> bool {
+  !name.is_empty()
+    && name.parse::<IpAddr>().is_err()
+    && name
+      .trim_end_matches('.')
+      .split('.')
+      .all(|label| {
+        !label.is_empty()
+          && label.len() <= 0x3F
+          && label.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+      })
+}
+
+// * >>> *
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn blocklist(text: &str) -> (Blocklist, usize) {
+    let mut blocklist: Blocklist = Blocklist::new();
+    let rules: usize = parse_blocklist(text, &mut blocklist, false);
+    (blocklist, rules)
+  }
+
+  // *
+
+  #[test]
+  fn reads_hosts_files() {
+    let (blocklist, rules) = blocklist(
+      "127.0.0.1 localhost\n\
+       0.0.0.0 ads.example.com tracker.example.com # trailing comment\n\
+       :: ads6.example.com\n\
+       192.168.1.10 printer.example.com\n",
+    );
+    assert_eq!(rules, 0x03);
+    assert!(blocklist.is_blocked("ads.example.com"));
+    assert!(blocklist.is_blocked("tracker.example.com"));
+    assert!(blocklist.is_blocked("ads6.example.com"));
+    // entries only block the name itself, and real addresses are not rules
+    assert!(!blocklist.is_blocked("www.ads.example.com"));
+    assert!(!blocklist.is_blocked("printer.example.com"));
+    assert!(!blocklist.is_blocked("localhost"));
+  }
+
+  #[test]
+  fn reads_adguard_rules() {
+    let (blocklist, rules) = blocklist(
+      "[Adblock Plus 2.0]\n\
+       ! comment\n\
+       ||ads.example.com^\n\
+       ||pixel.example.org^|\n\
+       @@||cdn.ads.example.com^\n\
+       ||example.net/path^\n\
+       ||example.net^$third-party\n\
+       example.com##.banner\n",
+    );
+    assert_eq!(rules, 0x03);
+    assert!(blocklist.is_blocked("ads.example.com"));
+    assert!(blocklist.is_blocked("www.ads.example.com"));
+    assert!(blocklist.is_blocked("pixel.example.org"));
+    assert!(!blocklist.is_blocked("cdn.ads.example.com"));
+    assert!(!blocklist.is_blocked("img.cdn.ads.example.com"));
+    assert!(!blocklist.is_blocked("example.net"));
+    assert!(!blocklist.is_blocked("example.com"));
+  }
+
+  #[test]
+  fn reads_plain_names_and_wildcards() {
+    let (blocklist, rules) = blocklist(
+      "# comment\n\
+       \n\
+       ads.example.com\n\
+       *.tracker.example.com\n\
+       not a rule\n\
+       bad_label!.example.com\n",
+    );
+    assert_eq!(rules, 0x02);
+    assert!(blocklist.is_blocked("ads.example.com"));
+    assert!(!blocklist.is_blocked("www.ads.example.com"));
+    // a wildcard covers the names below, not the name itself
+    assert!(blocklist.is_blocked("a.tracker.example.com"));
+    assert!(blocklist.is_blocked("a.b.tracker.example.com"));
+    assert!(!blocklist.is_blocked("tracker.example.com"));
+  }
+
+  #[test]
+  fn adds_exceptions_from_allow_lists() {
+    let (mut blocklist, _) = blocklist("||example.com^\n");
+    let rules: usize =
+      parse_blocklist("www.example.com\n*.cdn.example.com\n", &mut blocklist, true);
+    assert_eq!(rules, 0x02);
+    assert!(blocklist.is_blocked("example.com"));
+    assert!(!blocklist.is_blocked("www.example.com"));
+    assert!(!blocklist.is_blocked("img.cdn.example.com"));
+    assert!(blocklist.is_blocked("cdn.example.com"));
+  }
+}
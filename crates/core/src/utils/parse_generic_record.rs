@@ -0,0 +1,34 @@
+use crate::enums::BytePacketError as Error;
+
+// * >>> *
+
+pub fn parse_generic_record(value: &str) -> Result<(u16, Vec<u8>), Error> {
+  let invalid = || Error::Custom(format!("Invalid generic record '{}'", value));
+  let mut parts = value.split_whitespace();
+
+  // "TYPEnnn \# <length> <hex data>" (RFC 3597 section 5)
+  let qtype: u16 = parts
+    .next()
+    .and_then(|t| t.to_uppercase().strip_prefix("TYPE")?.parse().ok())
+    .ok_or_else(invalid)?;
+  if parts.next() != Some("\\#") {
+    return Err(invalid());
+  }
+  let length: usize = parts
+    .next()
+    .and_then(|l| l.parse().ok())
+    .ok_or_else(invalid)?;
+
+  let hex: String = parts.collect();
+  if hex.len() != length * 0x02 || !hex.is_ascii() {
+    return Err(invalid());
+  }
+
+  let data: Vec<u8> = (0..hex.len())
+    .step_by(0x02)
+    .map(|i| u8::from_str_radix(&hex[i..i + 0x02], 0x10))
+    .collect::<Result<_, _>>()
+    .map_err(|_| invalid())?;
+
+  Ok((qtype, data))
+}
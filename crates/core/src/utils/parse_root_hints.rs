@@ -0,0 +1,78 @@
+use crate::enums::BytePacketError as Error;
+use std::net::{IpAddr, SocketAddr};
+
+// * >>> *
+
+/// Built-in copy of the root hints (https://www.internic.net/domain/named.root)
+pub const ROOT_HINTS: &str = r#"
+.                        3600000      NS    A.ROOT-SERVERS.NET.
+A.ROOT-SERVERS.NET.      3600000      A     198.41.0.4
+A.ROOT-SERVERS.NET.      3600000      AAAA  2001:503:ba3e::2:30
+.                        3600000      NS    B.ROOT-SERVERS.NET.
+B.ROOT-SERVERS.NET.      3600000      A     170.247.170.2
+B.ROOT-SERVERS.NET.      3600000      AAAA  2801:1b8:10::b
+.                        3600000      NS    C.ROOT-SERVERS.NET.
+C.ROOT-SERVERS.NET.      3600000      A     192.33.4.12
+C.ROOT-SERVERS.NET.      3600000      AAAA  2001:500:2::c
+.                        3600000      NS    D.ROOT-SERVERS.NET.
+D.ROOT-SERVERS.NET.      3600000      A     199.7.91.13
+D.ROOT-SERVERS.NET.      3600000      AAAA  2001:500:2d::d
+.                        3600000      NS    E.ROOT-SERVERS.NET.
+E.ROOT-SERVERS.NET.      3600000      A     192.203.230.10
+E.ROOT-SERVERS.NET.      3600000      AAAA  2001:500:a8::e
+.                        3600000      NS    F.ROOT-SERVERS.NET.
+F.ROOT-SERVERS.NET.      3600000      A     192.5.5.241
+F.ROOT-SERVERS.NET.      3600000      AAAA  2001:500:2f::f
+.                        3600000      NS    G.ROOT-SERVERS.NET.
+G.ROOT-SERVERS.NET.      3600000      A     192.112.36.4
+G.ROOT-SERVERS.NET.      3600000      AAAA  2001:500:12::d0d
+.                        3600000      NS    H.ROOT-SERVERS.NET.
+H.ROOT-SERVERS.NET.      3600000      A     198.97.190.53
+H.ROOT-SERVERS.NET.      3600000      AAAA  2001:500:1::53
+.                        3600000      NS    I.ROOT-SERVERS.NET.
+I.ROOT-SERVERS.NET.      3600000      A     192.36.148.17
+I.ROOT-SERVERS.NET.      3600000      AAAA  2001:7fe::53
+.                        3600000      NS    J.ROOT-SERVERS.NET.
+J.ROOT-SERVERS.NET.      3600000      A     192.58.128.30
+J.ROOT-SERVERS.NET.      3600000      AAAA  2001:503:c27::2:30
+.                        3600000      NS    K.ROOT-SERVERS.NET.
+K.ROOT-SERVERS.NET.      3600000      A     193.0.14.129
+K.ROOT-SERVERS.NET.      3600000      AAAA  2001:7fd::1
+.                        3600000      NS    L.ROOT-SERVERS.NET.
+L.ROOT-SERVERS.NET.      3600000      A     199.7.83.42
+L.ROOT-SERVERS.NET.      3600000      AAAA  2001:500:9f::42
+.                        3600000      NS    M.ROOT-SERVERS.NET.
+M.ROOT-SERVERS.NET.      3600000      A     202.12.27.33
+M.ROOT-SERVERS.NET.      3600000      AAAA  2001:dc3::35
+"#;
+
+// *
+
+pub fn parse_root_hints(value: &str) -> Result<Vec<SocketAddr>, Error> {
+  let mut servers: Vec<SocketAddr> = Vec::new();
+
+  // "<name> [ttl] [class] <type> <data>" lines, comments start with ';'
+  for line in value.lines() {
+    let line: &str = line.split(';').next().unwrap_or_default();
+    let parts: Vec<&str> = line.split_whitespace().collect();
+
+    // the owner name is skipped, so only the record type can match
+    let Some(type_pos) = parts.iter().skip(0x01).position(|part| {
+      part.eq_ignore_ascii_case("A") || part.eq_ignore_ascii_case("AAAA")
+    }) else {
+      continue;
+    };
+
+    let address: IpAddr = parts
+      .get(type_pos + 0x02)
+      .and_then(|address| address.parse().ok())
+      .ok_or_else(|| Error::Custom(format!("Invalid root hint '{}'", line.trim())))?;
+    servers.push(SocketAddr::new(address, 53));
+  }
+
+  if servers.is_empty() {
+    return Err(Error::Custom("No root server addresses found".to_string()));
+  }
+
+  Ok(servers)
+}
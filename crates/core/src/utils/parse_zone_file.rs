@@ -0,0 +1,677 @@
+use super::{in_zone, parse_generic_record};
+use crate::{
+  enums::{BytePacketError as Error, DnsRecord, QueryClass, QueryType},
+  structs::Zone,
+};
+use std::{fs::read_to_string, mem::replace, path::Path, str::from_utf8};
+
+// * >>> *
+
+pub const MAX_INCLUDE_DEPTH: usize = 0x08;
+
+/// One logical entry of a master file: a line, or several lines joined by parentheses
+struct Entry {
+  line: usize,
+  blank_owner: bool,
+  tokens: Vec<String>,
+}
+
+/// Values an entry inherits from the entries before it
+struct State {
+  origin: String,
+  default_ttl: Option<u32>,
+  last_ttl: Option<u32>,
+  last_owner: Option<String>,
+}
+
+// *
+
+/// Loads a zone from an RFC 1035 master file. `$INCLUDE` paths are relative to the
+/// file that includes them.
+pub fn parse_zone_file(origin: &str, path: &Path) -> Result<Zone, Error> {
+  let mut zone: Zone = Zone::new(origin);
+  let mut state: State = State {
+    origin: zone.origin.clone(),
+    default_ttl: None,
+    last_ttl: None,
+    last_owner: None,
+  };
+  load_file(&mut zone, &mut state, path, 0)?;
+
+  let invalid = |message: String| {
+    Error::Custom(format!("Invalid zone '{}': {}", zone.origin, message))
+  };
+  if zone.rrset(&zone.origin, QueryType::SOA).len() != 0x01 {
+    return Err(invalid("exactly one SOA record is needed at the apex".to_string()));
+  }
+  for (owner, records) in &zone.records {
+    let has_cname: bool = records.iter().any(|r| r.qtype() == QueryType::CNAME);
+    if has_cname && records.len() > 0x01 {
+      return Err(invalid(format!("CNAME at '{}' can not have other records", owner)));
+    }
+  }
+
+  Ok(zone)
+}
+
+// *
+
+fn load_file(
+  zone: &mut Zone,
+  state: &mut State,
+  path: &Path,
+  depth: usize,
+) -> Result<(), Error> {
+  let text: String = read_to_string(path).map_err(|e| {
+    Error::Custom(format!("Failed to read zone file '{}': {}", path.display(), e))
+  })?;
+  let invalid = |line: usize, message: String| {
+    Error::Custom(format!(
+      "Invalid zone file '{}' (line {}): {}",
+      path.display(),
+      line,
+      message
+    ))
+  };
+
+  for entry in tokenize(&text).map_err(|(line, message)| invalid(line, message))? {
+    let directive: String = match entry.blank_owner {
+      true => String::new(),
+      false => entry.tokens[0].to_uppercase(),
+    };
+    let argument = |index: usize| {
+      entry
+        .tokens
+        .get(index)
+        .ok_or_else(|| invalid(entry.line, format!("missing {} argument", directive)))
+    };
+
+    match directive.as_str() {
+      "$ORIGIN" => state.origin = absolute_name(argument(0x01)?, &state.origin),
+      "$TTL" => {
+        let ttl: &String = argument(0x01)?;
+        let message: String = format!("invalid TTL '{}'", ttl);
+        let ttl: u32 = parse_ttl(ttl).ok_or_else(|| invalid(entry.line, message))?;
+        state.default_ttl = Some(ttl);
+      },
+      "$INCLUDE" => {
+        if depth >= MAX_INCLUDE_DEPTH {
+          return Err(invalid(entry.line, "too many nested includes".to_string()));
+        }
+        let origin: String = match entry.tokens.get(0x02) {
+          Some(origin) => absolute_name(origin, &state.origin),
+          None => state.origin.clone(),
+        };
+        let include: &Path =
+          &path.parent().unwrap_or(Path::new("")).join(argument(0x01)?);
+
+        // the origin of the including file is restored afterwards (RFC 1035 section 5.1)
+        let origin: String = replace(&mut state.origin, origin);
+        load_file(zone, state, include, depth + 0x01)?;
+        state.origin = origin;
+      },
+      _ if directive.starts_with('$') => {
+        return Err(invalid(entry.line, format!("unsupported directive {}", directive)));
+      },
+      _ => {
+        let record: DnsRecord =
+          parse_record(state, &entry).map_err(|message| invalid(entry.line, message))?;
+        if !in_zone(record.domain(), &zone.origin) {
+          let message: String = format!("'{}' is outside of the zone", record.domain());
+          return Err(invalid(entry.line, message));
+        }
+        zone.insert(record);
+      },
+    }
+  }
+
+  Ok(())
+}
+
+// *
+
+/// Splits a master file into entries, joining parenthesized lines and dropping comments.
+/// Quoted strings are single tokens and escapes are kept for the record parser.
+fn tokenize(text: &str) -> Result<Vec<Entry>, (usize, String)> {
+  let mut entries: Vec<Entry> = Vec::new();
+  let mut entry: Entry = Entry {
+    line: 0x01,
+    blank_owner: false,
+    tokens: Vec::new(),
+  };
+  let mut token: Option<Vec<u8>> = None;
+  let mut quoted: bool = false;
+  let mut depth: usize = 0;
+  let mut line: usize = 0x01;
+  let mut line_start: bool = true;
+
+  let finish = |tokens: &mut Vec<String>, token: &mut Option<Vec<u8>>| {
+    if let Some(token) = token.take() {
+      tokens.push(String::from_utf8_lossy(&token).into_owned());
+    }
+  };
+
+  let mut bytes = text.bytes().peekable();
+  while let Some(byte) = bytes.next() {
+    let at_line_start: bool = replace(&mut line_start, false);
+    match byte {
+      b'\\' => {
+        let current: &mut Vec<u8> = token.get_or_insert_with(Vec::new);
+        current.push(byte);
+        if let Some(escaped) = bytes.next_if(|escaped| *escaped != b'\n') {
+          current.push(escaped);
+        }
+      },
+      b'\n' if quoted => return Err((line, "unterminated string".to_string())),
+      b'"' if quoted => {
+        // an empty string is still a token
+        token.get_or_insert_with(Vec::new);
+        finish(&mut entry.tokens, &mut token);
+        quoted = false;
+      },
+      _ if quoted => token.get_or_insert_with(Vec::new).push(byte),
+      b'"' => {
+        finish(&mut entry.tokens, &mut token);
+        quoted = true;
+      },
+      b';' => {
+        finish(&mut entry.tokens, &mut token);
+        while bytes.next_if(|next| *next != b'\n').is_some() {}
+      },
+      b'(' => {
+        finish(&mut entry.tokens, &mut token);
+        depth += 0x01;
+      },
+      b')' => {
+        finish(&mut entry.tokens, &mut token);
+        depth = depth
+          .checked_sub(0x01)
+          .ok_or_else(|| (line, "unbalanced parentheses".to_string()))?;
+      },
+      b'\n' => {
+        finish(&mut entry.tokens, &mut token);
+        line += 0x01;
+        if depth == 0 {
+          let next: Entry = Entry {
+            line,
+            blank_owner: false,
+            tokens: Vec::new(),
+          };
+          let done: Entry = replace(&mut entry, next);
+          if !done.tokens.is_empty() {
+            entries.push(done);
+          }
+          line_start = true;
+        }
+      },
+      b' ' | b'\t' | b'\r' => {
+        // a line starting with a blank reuses the previous owner name
+        if at_line_start {
+          entry.blank_owner = true;
+        }
+        finish(&mut entry.tokens, &mut token);
+      },
+      _ => token.get_or_insert_with(Vec::new).push(byte),
+    }
+  }
+
+  if quoted {
+    return Err((line, "unterminated string".to_string()));
+  }
+  if depth > 0 {
+    return Err((entry.line, "unbalanced parentheses".to_string()));
+  }
+  finish(&mut entry.tokens, &mut token);
+  if !entry.tokens.is_empty() {
+    entries.push(entry);
+  }
+
+  Ok(entries)
+}
+
+// *
+
+/// Parses "[owner] [ttl] [class] <type> <rdata>", where the TTL and the class can
+/// come in any order
+fn parse_record(state: &mut State, entry: &Entry) -> Result<DnsRecord, String> {
+  let mut tokens = entry.tokens.iter().map(String::as_str).peekable();
+  let owner: String = match entry.blank_owner {
+    true => state.last_owner.clone().ok_or("missing owner name")?,
+    false => absolute_name(tokens.next().unwrap_or_default(), &state.origin),
+  };
+  state.last_owner = Some(owner.clone());
+
+  let mut ttl: Option<u32> = None;
+  let mut class: Option<QueryClass> = None;
+  while let Some(token) = tokens.peek() {
+    if ttl.is_none()
+      && let Some(value) = parse_ttl(token)
+    {
+      ttl = Some(value);
+    } else if class.is_none()
+      && let Some(value) = parse_class(token)
+    {
+      class = Some(value);
+    } else {
+      break;
+    }
+    tokens.next();
+  }
+  if class.is_some_and(|class| class != QueryClass::IN) {
+    return Err("only the IN class is supported".to_string());
+  }
+
+  let rtype: &str = tokens.next().ok_or("missing record type")?;
+  let qtype: u16 =
+    record_type(rtype).ok_or_else(|| format!("unsupported record type '{}'", rtype))?;
+  let rdata: Vec<&str> = tokens.collect();
+  let mut record: DnsRecord = make_record(owner, qtype, &rdata, &state.origin)
+    .ok_or_else(|| format!("invalid {} record data '{}'", rtype, rdata.join(" ")))?;
+
+  // a missing TTL is the $TTL default, or the last one given (RFC 2308 section 4)
+  if ttl.is_some() {
+    state.last_ttl = ttl;
+  }
+  let ttl: u32 = match (ttl.or(state.default_ttl).or(state.last_ttl), &record) {
+    (Some(ttl), _) => ttl,
+    (None, DnsRecord::SOA { minimum, .. }) => *minimum,
+    (None, _) => return Err("missing TTL and no $TTL default".to_string()),
+  };
+  record.set_ttl(ttl);
+
+  Ok(record)
+}
+
+fn make_record(
+  domain: String,
+  qtype: u16,
+  rdata: &[&str],
+  origin: &str,
+) -> Option<DnsRecord> {
+  const CLASS: QueryClass = QueryClass::IN;
+
+  // the RFC 3597 generic notation is accepted for every type
+  if rdata.first() == Some(&"\\#") {
+    let (qtype, data): (u16, Vec<u8>) =
+      parse_generic_record(&format!("TYPE{} {}", qtype, rdata.join(" "))).ok()?;
+    return Some(DnsRecord::UNKNOWN {
+      data,
+      domain,
+      qtype,
+      class: CLASS,
+      ttl: 0,
+    });
+  }
+
+  let qtype: QueryType = QueryType::from(qtype);
+  let expected: usize = match qtype {
+    QueryType::TXT => rdata.len().max(0x01),
+    QueryType::SOA => 0x07,
+    QueryType::SRV => 0x04,
+    QueryType::MX => 0x02,
+    _ => 0x01,
+  };
+  if rdata.len() != expected {
+    return None;
+  }
+
+  let name = |index: usize| absolute_name(rdata[index], origin);
+  let number = |index: usize| rdata[index].parse::<u16>().ok();
+  let ttl = |index: usize| parse_ttl(rdata[index]);
+
+  let record: DnsRecord = match qtype {
+    QueryType::A => DnsRecord::A {
+      address: rdata[0].parse().ok()?,
+      domain,
+      class: CLASS,
+      ttl: 0,
+    },
+    QueryType::AAAA => DnsRecord::AAAA {
+      address: rdata[0].parse().ok()?,
+      domain,
+      class: CLASS,
+      ttl: 0,
+    },
+    QueryType::NS => DnsRecord::NS {
+      domain,
+      host: name(0),
+      class: CLASS,
+      ttl: 0,
+    },
+    QueryType::CNAME => DnsRecord::CNAME {
+      domain,
+      host: name(0),
+      class: CLASS,
+      ttl: 0,
+    },
+    QueryType::PTR => DnsRecord::PTR {
+      domain,
+      host: name(0),
+      class: CLASS,
+      ttl: 0,
+    },
+    QueryType::MX => DnsRecord::MX {
+      priority: number(0)?,
+      domain,
+      host: name(0x01),
+      class: CLASS,
+      ttl: 0,
+    },
+    QueryType::SRV => DnsRecord::SRV {
+      priority: number(0)?,
+      weight: number(0x01)?,
+      port: number(0x02)?,
+      domain,
+      host: name(0x03),
+      class: CLASS,
+      ttl: 0,
+    },
+    QueryType::SOA => DnsRecord::SOA {
+      domain,
+      mname: name(0),
+      rname: name(0x01),
+      serial: rdata[0x02].parse().ok()?,
+      refresh: ttl(0x03)?,
+      retry: ttl(0x04)?,
+      expire: ttl(0x05)?,
+      minimum: ttl(0x06)?,
+      class: CLASS,
+      ttl: 0,
+    },
+    QueryType::TXT => DnsRecord::TXT {
+      domain,
+      // character strings are at most 255 bytes, longer ones are split
+      data: rdata
+        .iter()
+        .map(|value| unescape(value))
+        .flat_map(|value| match value.is_empty() {
+          true => vec![value],
+          false => value.chunks(0xFF).map(<[u8]>::to_vec).collect(),
+        })
+        .collect(),
+      class: CLASS,
+      ttl: 0,
+    },
+    _ => return None,
+  };
+
+  Some(record)
+}
+
+// *
+
+fn record_type(value: &str) -> Option<u16> {
+  let value: String = value.to_uppercase();
+  let qtype: QueryType = match value.as_str() {
+    "A" => QueryType::A,
+    "NS" => QueryType::NS,
+    "CNAME" => QueryType::CNAME,
+    "SOA" => QueryType::SOA,
+    "PTR" => QueryType::PTR,
+    "MX" => QueryType::MX,
+    "TXT" => QueryType::TXT,
+    "AAAA" => QueryType::AAAA,
+    "SRV" => QueryType::SRV,
+    _ => return value.strip_prefix("TYPE")?.parse().ok(),
+  };
+  Some(qtype.to_u16())
+}
+
+fn parse_class(value: &str) -> Option<QueryClass> {
+  match value.to_uppercase().as_str() {
+    "IN" => Some(QueryClass::IN),
+    "CS" => Some(QueryClass::UNKNOWN(0x02)),
+    "CH" => Some(QueryClass::CH),
+    "HS" => Some(QueryClass::HS),
+    _ => None,
+  }
+}
+
+/// Parses a TTL in seconds, or with BIND style units ("1w2d3h4m5s")
+fn parse_ttl(value: &str) -> Option<u32> {
+  if !value.starts_with(|c: char| c.is_ascii_digit()) {
+    return None;
+  }
+
+  let mut total: u32 = 0;
+  let mut number: u32 = 0;
+  for c in value.chars() {
+    let seconds: u32 = match c.to_ascii_lowercase() {
+      's' => 0x01,
+      'm' => 0x3C,
+      'h' => 0xE10,
+      'd' => 0x15180,
+      'w' => 0x93A80,
+      _ => {
+        number = number.checked_mul(0x0A)?.checked_add(c.to_digit(0x0A)?)?;
+        continue;
+      },
+    };
+    total = total.checked_add(number.checked_mul(seconds)?)?;
+    number = 0;
+  }
+
+  total.checked_add(number)
+}
+
+/// Makes a name absolute: "@" is the origin, names without a trailing dot are
+/// relative to it
+fn absolute_name(name: &str, origin: &str) -> String {
+  if name == "@" {
+    return origin.to_string();
+  }
+
+  match name.strip_suffix('.') {
+    Some(name) => name.to_string(),
+    None if origin.is_empty() => name.to_string(),
+    None => format!("{}.{}", name, origin),
+  }
+}
+
+/// Resolves the escapes of a character string: "\DDD" is a decimal byte value and
+/// "\X" is X itself
+fn unescape(value: &str) -> Vec<u8> {
+  let bytes: &[u8] = value.as_bytes();
+  let mut result: Vec<u8> = Vec::new();
+  let mut i: usize = 0;
+
+  while i < bytes.len() {
+    if bytes[i] == b'\\' {
+      let decimal: Option<u8> = bytes
+        .get(i + 0x01..i + 0x04)
+        .filter(|digits| digits.iter().all(u8::is_ascii_digit))
+        .and_then(|digits| from_utf8(digits).ok()?.parse().ok());
+      if let Some(byte) = decimal {
+        result.push(byte);
+        i += 0x04;
+        continue;
+      }
+      if let Some(byte) = bytes.get(i + 0x01) {
+        result.push(*byte);
+        i += 0x02;
+        continue;
+      }
+    }
+
+    result.push(bytes[i]);
+    i += 0x01;
+  }
+
+  result
+}
+
+// * >>> *
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::{
+    fs::{create_dir_all, write},
+    net::Ipv4Addr,
+    path::PathBuf,
+    process,
+  };
+
+  const SOA: &str = "@ 3600 IN SOA ns1 hostmaster 1 2h 15m 1w 300\n";
+
+  /// Writes the files of a test in their own directory, returning the first one
+  fn zone_files(test: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir: PathBuf =
+      std::env::temp_dir().join(format!("dns-zone-{}-{}", process::id(), test));
+    create_dir_all(&dir).unwrap();
+    for (name, text) in files {
+      write(dir.join(name), text).unwrap();
+    }
+    dir.join(files[0].0)
+  }
+
+  fn load(test: &str, files: &[(&str, &str)]) -> Result<Zone, Error> {
+    parse_zone_file("example.test", &zone_files(test, files))
+  }
+
+  fn error(result: Result<Zone, Error>) -> String {
+    match result {
+      Err(Error::Custom(message)) => message,
+      other => panic!("expected an error, got {:?}", other),
+    }
+  }
+
+  // *
+
+  #[test]
+  fn joins_parentheses_and_reuses_blank_owners() {
+    let zone: Zone = load("parens", &[(
+      "db",
+      "@ IN SOA ns1 hostmaster (\n  2024 ; serial\n  2h 15m\n  1w 300 )\n\
+       \x20 300 IN NS ns1\n\
+       \tIN NS ns2.example.test.\n\
+       mail 60 A 192.0.2.25\n\
+       \x20 60 A 192.0.2.26\n",
+    )])
+    .unwrap();
+
+    let Some(DnsRecord::SOA {
+      serial,
+      refresh,
+      retry,
+      expire,
+      minimum,
+      ttl,
+      ..
+    }) = zone.soa()
+    else {
+      panic!("missing SOA");
+    };
+    assert_eq!((serial, refresh, retry, expire, minimum), (2024, 7200, 900, 604800, 300));
+    // without a TTL nor $TTL, the SOA takes its minimum
+    assert_eq!(ttl, 300);
+
+    // the second NS takes the TTL of the first one
+    let hosts: Vec<(String, u32)> = zone
+      .rrset("example.test", QueryType::NS)
+      .into_iter()
+      .filter_map(|record| match record {
+        DnsRecord::NS { host, ttl, .. } => Some((host, ttl)),
+        _ => None,
+      })
+      .collect();
+    assert_eq!(hosts, [
+      ("ns1.example.test".to_string(), 300),
+      ("ns2.example.test".to_string(), 300),
+    ]);
+    assert_eq!(zone.rrset("mail.example.test", QueryType::A).len(), 0x02);
+  }
+
+  #[test]
+  fn applies_origin_ttl_and_include_directives() {
+    let zone: Zone = load("directives", &[
+      (
+        "db",
+        "$TTL 1h\n$ORIGIN example.test.\n@ SOA ns1 hostmaster 1 1 1 1 1\n\
+         $INCLUDE inc.zone sub\n\
+         after A 192.0.2.2\n\
+         $ORIGIN other.example.test.\n\
+         x 30 A 192.0.2.3\n",
+      ),
+      ("inc.zone", "host A 192.0.2.1\n@ TXT \"included\"\n"),
+    ])
+    .unwrap();
+
+    let host: Vec<DnsRecord> = zone.rrset("host.sub.example.test", QueryType::A);
+    assert_eq!(host, [DnsRecord::A {
+      address: Ipv4Addr::new(192, 0, 2, 1),
+      domain: "host.sub.example.test".to_string(),
+      class: QueryClass::IN,
+      ttl: 3600,
+    }]);
+    assert_eq!(zone.rrset("sub.example.test", QueryType::TXT).len(), 0x01);
+
+    // the origin of the including file is restored after the include
+    assert_eq!(zone.rrset("after.example.test", QueryType::A).len(), 0x01);
+    assert!(zone.lookup("after.sub.example.test").is_none());
+    assert_eq!(zone.rrset("x.other.example.test", QueryType::A)[0].ttl(), 30);
+  }
+
+  #[test]
+  fn reads_escapes_and_ttl_units() {
+    let zone: Zone = load("escapes", &[(
+      "db",
+      "$TTL 1h30m\n@ SOA ns1 hostmaster 1 1 1 1 1\n\
+       txt TXT \"semi\\059colon\" \"quote\\\"d\" plain\\ space\n\
+       week 1W A 192.0.2.1\n",
+    )])
+    .unwrap();
+
+    let txt: Vec<DnsRecord> = zone.rrset("txt.example.test", QueryType::TXT);
+    let Some(DnsRecord::TXT { data, ttl, .. }) = txt.first() else {
+      panic!("missing TXT");
+    };
+    assert_eq!(data, &[
+      b"semi;colon".to_vec(),
+      b"quote\"d".to_vec(),
+      b"plain space".to_vec(),
+    ]);
+    assert_eq!(*ttl, 5400);
+    assert_eq!(zone.rrset("week.example.test", QueryType::A)[0].ttl(), 604800);
+
+    assert_eq!(parse_ttl("300"), Some(300));
+    assert_eq!(parse_ttl("1d2h3m4s"), Some(93784));
+    assert_eq!(parse_ttl("IN"), None);
+    assert_eq!(parse_ttl("1x"), None);
+  }
+
+  #[test]
+  fn rejects_cname_with_other_data() {
+    let message: String = error(load("cname", &[(
+      "db",
+      &format!("{}www CNAME @\nwww A 192.0.2.1\n", SOA),
+    )]));
+    assert!(message.contains("CNAME at 'www.example.test'"), "{}", message);
+  }
+
+  #[test]
+  fn rejects_zones_without_a_single_soa() {
+    let missing: String = error(load("no-soa", &[("db", "$TTL 60\n@ A 192.0.2.1\n")]));
+    assert!(missing.contains("exactly one SOA"), "{}", missing);
+
+    let twice: String = error(load("two-soa", &[(
+      "db",
+      &format!("{}@ SOA ns2 hostmaster 2 1 1 1 1\n", SOA),
+    )]));
+    assert!(twice.contains("exactly one SOA"), "{}", twice);
+  }
+
+  #[test]
+  fn reports_lines_of_invalid_entries() {
+    let outside: String = error(load("outside", &[(
+      "db",
+      &format!("{}host.example.org. A 192.0.2.1\n", SOA),
+    )]));
+    assert!(outside.contains("(line 2)"), "{}", outside);
+    assert!(outside.contains("outside of the zone"), "{}", outside);
+
+    let unbalanced: String =
+      error(load("unbalanced", &[("db", "@ SOA ns1 hostmaster ( 1 1 1 1 1\n")]));
+    assert!(unbalanced.contains("unbalanced parentheses"), "{}", unbalanced);
+  }
+}
@@ -0,0 +1,20 @@
+use std::net::IpAddr;
+
+// * >>> *
+
+pub fn reverse_name(addr: IpAddr) -> String {
+  match addr {
+    IpAddr::V4(ipv4) => {
+      let [a, b, c, d] = ipv4.octets();
+      format!("{}.{}.{}.{}.in-addr.arpa", d, c, b, a)
+    },
+    IpAddr::V6(ipv6) => {
+      let mut name: String = String::with_capacity(0x48);
+      for octet in ipv6.octets().iter().rev() {
+        name.push_str(&format!("{:x}.{:x}.", octet & 0x0F, octet >> 0x04));
+      }
+      name.push_str("ip6.arpa");
+      name
+    },
+  }
+}
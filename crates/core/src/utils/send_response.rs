@@ -2,19 +2,21 @@ use crate::{
   enums::BytePacketError as Error,
   structs::{BytePacketBuffer as Buffer, DnsPacket},
 };
-use std::net::{SocketAddr, UdpSocket};
+use std::net::SocketAddr;
+use tokio::net::UdpSocket;
 
 // * >>> *
 
-pub fn send_response(
+pub async fn send_response(
   socket: &UdpSocket,
   response: &mut DnsPacket,
   src: SocketAddr,
+  max_size: usize,
 ) -> Result<(), Error> {
   let mut res_buffer: Buffer = Buffer::new();
-  response.write(&mut res_buffer)?;
+  response.write_truncated(&mut res_buffer, max_size)?;
 
   let data: &[u8] = res_buffer.get_range(0, res_buffer.pos())?;
-  socket.send_to(data, src)?;
+  socket.send_to(data, src).await?;
   Ok(())
 }